@@ -0,0 +1,144 @@
+use super::direction::{Direction, DirectionSet};
+use super::rng::GameRng;
+use super::spawn::Spawn;
+
+/// The number of illegal moves in a row after which `tfe::Game::run` gives up on an agent.
+///
+/// This applies to the closures passed to `tfe::Game::play` as well. A closure is asked again up
+/// to 64 times before an illegal move counts towards this limit, so a closure that ignores
+/// `failed` and picks random moves still plays its game to completion while one that keeps
+/// returning the same illegal move ends the game after a few hundred calls.
+pub const ILLEGAL_MOVE_LIMIT: usize = 4;
+
+/// The number of times a `tfe::Game::play` closure is asked for a move before its illegal move
+/// counts towards `tfe::ILLEGAL_MOVE_LIMIT`.
+const FN_AGENT_RETRIES: usize = 64;
+
+/// Struct passed to an `tfe::Agent` describing the game it is playing.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameState {
+    /// The current board.
+    pub board: u64,
+    /// The score of the game so far.
+    pub score: u64,
+    /// The number of moves that changed the board so far.
+    pub moves: u64,
+    /// The tile that spawned after the previous move, `None` before the first move.
+    pub last_spawn: Option<Spawn>,
+    /// The moves that change the current board.
+    pub legal: DirectionSet
+}
+
+/// Reason returned by `tfe::Game::run` for ending a game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameEnd {
+    /// No move can change the board anymore.
+    Over,
+    /// The agent chose `tfe::ILLEGAL_MOVE_LIMIT` illegal moves in a row.
+    IllegalMoves
+}
+
+/// Trait implemented by anything that can play a game of 2048.
+///
+/// Since `choose` takes `&mut self`, an agent is free to keep state between moves. `reset` is
+/// called before a game starts and `on_game_end` after it ended, which allows a single agent to
+/// play many games in a row.
+///
+/// # Examples
+///
+/// An agent that cycles through the legal moves:
+///
+/// ```
+/// use tfe::{Agent, Direction, Game, GameState};
+///
+/// struct Cycle { turn: usize }
+///
+/// impl Agent for Cycle {
+///     fn choose(&mut self, state: &GameState) -> Direction {
+///         let legal = state.legal.to_vec();
+///         self.turn += 1;
+///         legal[self.turn % legal.len()]
+///     }
+///
+///     fn reset(&mut self) {
+///         self.turn = 0;
+///     }
+/// }
+///
+/// let mut game = Game::new();
+/// game.run(&mut Cycle { turn: 0 });
+///
+/// assert!(Game::is_over(game.board));
+/// ```
+pub trait Agent {
+    /// Returns the `Direction` to move in given the current `state` of the game.
+    /// Choosing a move that is not in `state.legal` does not change the game and `choose` will
+    /// be called again with the same `state`.
+    fn choose(&mut self, state: &GameState) -> Direction;
+
+    /// Called before a new game is started.
+    fn reset(&mut self) {}
+
+    /// Called with the final state of a game after it ended.
+    fn on_game_end(&mut self, _state: &GameState) {}
+
+    /// Reseeds any random number generator used by this agent so that it makes the same
+    /// decisions for the same `seed`. Called by `tfe::sim` before every game it plays with a seed
+    /// derived from the seed of that game, which in a seeded batch makes the agent's decisions
+    /// reproducible as well.
+    fn seed(&mut self, _seed: u64) {}
+}
+
+impl<A: Agent + ?Sized> Agent for Box<A> {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        (**self).choose(state)
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+
+    fn on_game_end(&mut self, state: &GameState) {
+        (**self).on_game_end(state)
+    }
+
+    fn seed(&mut self, seed: u64) {
+        (**self).seed(seed)
+    }
+}
+
+/// Trait implemented by policies that choose moves during rollouts of search agents such as
+/// `tfe::mcts::Mcts`. Rollouts play many moves, so a policy should be cheap to evaluate.
+pub trait RolloutPolicy {
+    /// Returns one of the `legal` moves on `board`, any random decision is drawn from `rng`.
+    fn choose(&mut self, board: u64, legal: DirectionSet, rng: &mut GameRng) -> Direction;
+}
+
+/// `RolloutPolicy` that picks a random legal move using `tfe::Direction::sample_without_rng`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RandomRollout;
+
+impl RolloutPolicy for RandomRollout {
+    fn choose(&mut self, _board: u64, legal: DirectionSet, rng: &mut GameRng) -> Direction {
+        Direction::sample_without_rng(&legal.complement().to_vec(), rng)
+    }
+}
+
+/// Adapter used by `tfe::Game::play` to run a closure as an `Agent`.
+/// The closure receives the board and the list of moves that do not change the board, it is
+/// called again up to `FN_AGENT_RETRIES` times until it returns a move that does change the board.
+pub(crate) struct FnAgent<F>(pub F);
+
+impl<F: FnMut(u64, &Vec<Direction>) -> Direction> Agent for FnAgent<F> {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        let failed        = state.legal.complement().to_vec();
+        let mut direction = (self.0)(state.board, &failed);
+
+        for _ in 1 .. FN_AGENT_RETRIES {
+            if state.legal.contains(direction) { break }
+            direction = (self.0)(state.board, &failed);
+        }
+
+        direction
+    }
+}
@@ -0,0 +1,161 @@
+//! Baseline agents that are commonly used to compare other agents against.
+//!
+//! Every agent implements `tfe::Agent`, so they can be played using `tfe::Game::run`:
+//!
+//! ```
+//! use tfe::Game;
+//! use tfe::agents::{Corner, Greedy, Random, Snake};
+//!
+//! let mut game = Game::new();
+//!
+//! game.run(&mut Random::new());
+//! game.run(&mut Greedy);
+//! game.run(&mut Corner);
+//! game.run(&mut Snake::default());
+//! ```
+
+use super::rand::{thread_rng, Rng};
+
+use super::agent::{Agent, GameState};
+use super::ai::best;
+use super::direction::Direction;
+use super::game::Game;
+use super::rng::GameRng;
+
+/// The cell that `Direction::Down` followed by `Direction::Left` moves tiles towards.
+///
+/// ```
+/// use tfe::{Direction, Game};
+/// use tfe::agents::CORNER_CELL;
+///
+/// let board = Game::execute(Game::execute(0x0000_0000_0010_0000, &Direction::Down), &Direction::Left);
+/// assert_eq!(board, 1 << (CORNER_CELL * 4));
+/// ```
+pub const CORNER_CELL: u8 = 3;
+
+/// Default priority of `tfe::agents::Snake`.
+pub const SNAKE_ORDER: [Direction; 4] = [Direction::Down, Direction::Left, Direction::Right, Direction::Up];
+
+/// Agent that picks a random legal move.
+pub struct Random {
+    rng: GameRng
+}
+
+impl Random {
+    /// Constructs a new `tfe::agents::Random` seeded from `rand::thread_rng`.
+    pub fn new() -> Self {
+        Self::seeded(thread_rng().gen())
+    }
+
+    /// Constructs a new `tfe::agents::Random` that picks the same moves for the same `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Random { rng: GameRng::new(seed) }
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for Random {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        Direction::sample_without_rng(&state.legal.complement().to_vec(), &mut self.rng)
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = GameRng::new(seed);
+    }
+}
+
+/// Agent that picks the legal move with the highest merge score. Ties go to the first move in
+/// the order left, right, up, down.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::{Agent, Direction, Game};
+/// use tfe::agents::Greedy;
+///
+/// let state = Game::from_board(0x0000_0000_0001_1022).state();
+/// assert_eq!(Greedy.choose(&state), Direction::Left);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Greedy;
+
+impl Agent for Greedy {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        let scores: Vec<(Direction, f64)> = state.legal.iter()
+            .map(|direction| (direction, Game::execute_scored(state.board, &direction).1 as f64))
+            .collect();
+
+        if scores.is_empty() { Direction::Left } else { best(&scores) }
+    }
+}
+
+/// Agent that tries to keep the highest tile in `tfe::agents::CORNER_CELL`.
+///
+/// Moves that leave the highest tile in the corner are preferred, then moves with the highest
+/// merge score. Remaining ties go to the first move in the order down, left, right, up.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Corner;
+
+impl Corner {
+    /// Returns `true` if the highest tile of `board` is in `tfe::agents::CORNER_CELL`.
+    fn cornered(board: u64) -> bool {
+        let highest = (0 .. 16).map(|cell| (board >> (cell * 4)) & 0xF).max().unwrap_or(0);
+
+        highest > 0 && (board >> (CORNER_CELL * 4)) & 0xF == highest
+    }
+}
+
+impl Agent for Corner {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        let mut top: Option<(Direction, bool, u64)> = None;
+
+        for &direction in &SNAKE_ORDER {
+            if !state.legal.contains(direction) { continue }
+
+            let (after, score) = Game::execute_scored(state.board, &direction);
+            let cornered       = Self::cornered(after);
+
+            if top.is_none_or(|(_, c, s)| (cornered, score) > (c, s)) { top = Some((direction, cornered, score)) }
+        }
+
+        top.map(|(direction, _, _)| direction).unwrap_or(Direction::Left)
+    }
+}
+
+/// Agent that always picks the first legal move in a fixed priority order. The default order
+/// down, left, right, up builds a snake of tiles along the edges next to the corner and only
+/// moves up when nothing else is possible.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::{Agent, Direction, Game};
+/// use tfe::agents::Snake;
+///
+/// let mut agent = Snake { order: [Direction::Up, Direction::Right, Direction::Left, Direction::Down] };
+/// let state     = Game::from_board(0x0000_0000_0000_1234).state();
+///
+/// assert_eq!(agent.choose(&state), Direction::Up);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Snake {
+    /// The order in which moves are tried.
+    pub order: [Direction; 4]
+}
+
+impl Default for Snake {
+    fn default() -> Self {
+        Snake { order: SNAKE_ORDER }
+    }
+}
+
+impl Agent for Snake {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        self.order.iter().cloned().find(|&direction| state.legal.contains(direction)).unwrap_or(Direction::Left)
+    }
+}
@@ -0,0 +1,240 @@
+//! Agents that search for the best move using the heuristic in `tfe::heuristic`.
+//!
+//! - `OnePly` scores the board after each legal move and picks the best one.
+//! - `Expectimax` searches several moves ahead, averaging over every possible tile spawn.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::agent::{Agent, GameState};
+use super::direction::Direction;
+use super::game::Game;
+use super::heuristic::{Heuristic, HEURISTIC};
+use super::spawn::SpawnRules;
+
+/// Penalty subtracted from the heuristic score of a board on which no move is possible.
+pub const LOST_PENALTY: f64 = 1_000_000.0;
+
+/// Returns the legal move with the highest score in `scores`, ties go to the first move.
+pub(crate) fn best(scores: &[(Direction, f64)]) -> Direction {
+    let mut best = scores[0];
+
+    for &(direction, score) in &scores[1 ..] {
+        if score > best.1 { best = (direction, score) }
+    }
+
+    best.0
+}
+
+/// Agent that moves in the direction whose resulting board has the highest heuristic score.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::Game;
+/// use tfe::ai::OnePly;
+///
+/// let mut game = Game::new();
+/// game.run(&mut OnePly::new());
+///
+/// assert!(Game::is_over(game.board));
+/// ```
+pub struct OnePly { heuristic: Arc<Heuristic> }
+
+impl Default for OnePly {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OnePly {
+    /// Constructs a new `tfe::ai::OnePly` using the default heuristic.
+    pub fn new() -> Self {
+        Self::with_heuristic(HEURISTIC.clone())
+    }
+
+    /// Constructs a new `tfe::ai::OnePly` using `heuristic` to score boards.
+    pub fn with_heuristic(heuristic: Arc<Heuristic>) -> Self {
+        OnePly { heuristic }
+    }
+
+    /// Returns the heuristic score of `board` after each legal move.
+    pub fn scores(&self, board: u64) -> Vec<(Direction, f64)> {
+        Game::legal_moves(board).iter()
+            .map(|direction| (direction, self.heuristic.evaluate(Game::execute(board, &direction))))
+            .collect()
+    }
+}
+
+impl Agent for OnePly {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        best(&self.scores(state.board))
+    }
+}
+
+/// Agent that performs an expectimax search over moves and tile spawns.
+///
+/// Move nodes pick the best move, chance nodes average over every empty tile and every tile
+/// value the `rules` can spawn, weighted by their probability. When `rules` spawn several tiles
+/// per move, every spawned tile adds a level of chance nodes, which makes the search a lot
+/// slower. A branch stops being searched
+/// when `depth` moves have been made or when the probability of reaching it drops below
+/// `cutoff`, at which point the board is scored by the heuristic. Scores of boards that were
+/// already searched as deep or deeper are reused from a transposition cache.
+///
+/// This is a port of the search used by [nneonneo's 2048 AI](https://github.com/nneonneo/2048-ai).
+///
+/// # Examples
+///
+/// ```
+/// use tfe::{Agent, Direction, Game};
+/// use tfe::ai::Expectimax;
+///
+/// // | 0 | 0 | 0 | 0 |
+/// // | 0 | 0 | 0 | 0 |
+/// // | 0 | 0 | 0 | 0 |
+/// // | 2 | 4 | 8 | 16 |
+///
+/// let mut agent = Expectimax::with_depth(2);
+/// let state     = Game::from_board(0x0000_0000_0000_1234).state();
+///
+/// assert_eq!(agent.choose(&state), Direction::Up);
+///
+/// // search a variant where two tiles spawn after every move
+/// agent.rules.tiles_per_move = 2;
+///
+/// assert_eq!(agent.choose(&state), Direction::Up);
+/// ```
+pub struct Expectimax {
+    /// Maximum number of moves to search ahead.
+    pub depth: u32,
+    /// Branches reached with a probability below this value are not searched any further.
+    pub cutoff: f64,
+    /// The rules used to generate the outcomes of chance nodes.
+    pub rules: SpawnRules,
+
+    heuristic: Arc<Heuristic>,
+    cache:     HashMap<u64, (u32, f64)>
+}
+
+impl Default for Expectimax {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Expectimax {
+    /// Constructs a new `tfe::ai::Expectimax` searching 3 moves ahead with a cutoff of `0.0001`.
+    pub fn new() -> Self {
+        Self::with_depth(3)
+    }
+
+    /// Like `tfe::ai::Expectimax::new` but searches `depth` moves ahead.
+    pub fn with_depth(depth: u32) -> Self {
+        Expectimax {
+            depth,
+            cutoff:    0.0001,
+            rules:     SpawnRules::default(),
+            heuristic: HEURISTIC.clone(),
+            cache:     HashMap::new()
+        }
+    }
+
+    /// Replaces the heuristic used to score the leaves of the search tree.
+    pub fn set_heuristic(&mut self, heuristic: Arc<Heuristic>) {
+        self.heuristic = heuristic;
+        self.cache.clear();
+    }
+
+    /// Returns the expected score of `board` after each legal move.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `rules` are invalid, see `tfe::SpawnRules::validate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::Direction;
+    /// use tfe::ai::Expectimax;
+    ///
+    /// let scores = Expectimax::with_depth(1).scores(0x0000_0000_0000_1234);
+    ///
+    /// assert_eq!(scores.len(), 1);
+    /// assert_eq!(scores[0].0, Direction::Up);
+    /// ```
+    pub fn scores(&mut self, board: u64) -> Vec<(Direction, f64)> {
+        if let Err(err) = self.rules.validate() { panic!("invalid spawn rules: {}", err) }
+
+        self.cache.clear();
+
+        Game::legal_moves(board).iter()
+            .map(|direction| (direction, self.chance_node(Game::execute(board, &direction), 1.0, 1)))
+            .collect()
+    }
+
+    /// Returns the score of the best move on `board`.
+    fn move_node(&mut self, board: u64, probability: f64, depth: u32) -> f64 {
+        let legal = Game::legal_moves(board);
+
+        if legal.is_empty() { return self.heuristic.evaluate(board) - LOST_PENALTY }
+
+        legal.iter()
+            .map(|direction| self.chance_node(Game::execute(board, &direction), probability, depth + 1))
+            .fold(f64::MIN, f64::max)
+    }
+
+    /// Returns the expected score of `board` averaged over every possible spawn of the
+    /// `tiles_per_move` tiles that spawn after a move.
+    fn chance_node(&mut self, board: u64, probability: f64, depth: u32) -> f64 {
+        if depth >= self.depth || probability < self.cutoff {
+            return self.heuristic.evaluate(board);
+        }
+
+        if let Some(&(cached_depth, score)) = self.cache.get(&board) {
+            if cached_depth <= depth { return score }
+        }
+
+        let score = self.spawn_node(board, probability, depth, self.rules.tiles_per_move);
+        self.cache.insert(board, (depth, score));
+
+        score
+    }
+
+    /// Returns the expected score of `board` averaged over every possible spawn of `tiles` more
+    /// tiles, each spawned tile is another level of chance nodes.
+    fn spawn_node(&mut self, board: u64, probability: f64, depth: u32, tiles: usize) -> f64 {
+        let empty = Game::count_empty(board) as f64;
+
+        if tiles == 0 || empty == 0.0 { return self.move_node(board, probability, depth) }
+
+        let outcomes    = self.rules.outcomes();
+        let probability = probability / empty;
+        let mut total   = 0.0;
+        let mut tmp     = board;
+        let mut tile    = 1;
+
+        while tile != 0 {
+            if tmp & 0xF == 0 {
+                for &(value, chance) in &outcomes {
+                    total += chance * self.spawn_node(board | (tile * value), probability * chance, depth, tiles - 1);
+                }
+            }
+
+            tmp  >>= 4;
+            tile <<= 4;
+        }
+
+        total / empty
+    }
+}
+
+impl Agent for Expectimax {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        best(&self.scores(state.board))
+    }
+
+    fn reset(&mut self) {
+        self.cache.clear();
+    }
+}
@@ -0,0 +1,358 @@
+#[macro_use]
+extern crate clap;
+
+extern crate libc;
+extern crate rand;
+extern crate tfe;
+
+mod play;
+mod replay;
+mod save;
+mod stats;
+mod terminal;
+mod train;
+mod tune;
+mod view;
+
+use clap::ArgMatches;
+
+use tfe::{Agent, Direction, GameState};
+use tfe::agents::{Corner, Greedy, Random, Snake};
+use tfe::ai::{Expectimax, OnePly};
+use tfe::mcts::{Budget, Mcts};
+use tfe::rollout::MonteCarlo;
+use tfe::heuristic::{Heuristic, HeuristicParams};
+use tfe::ntuple::NTupleNetwork;
+use tfe::sim::{run_batch, run_game, BatchConfig};
+use tfe::weights::WeightFile;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::Duration;
+use std::sync::Arc;
+
+/// The agent used to play simulated games, shared between threads.
+#[derive(Clone)]
+enum Player {
+    Random,
+    Greedy,
+    Corner,
+    Snake,
+    Heuristic(Arc<Heuristic>),
+    Expectimax(Arc<Heuristic>, u32),
+    Mcts(Budget),
+    Rollout(u32),
+    NTuple(Arc<NTupleNetwork>)
+}
+
+/// Agent that plays using an n-tuple network shared between threads.
+struct SharedNetwork(Arc<NTupleNetwork>);
+
+impl Agent for SharedNetwork {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        self.0.best_move(state.board).map(|(direction, _, _)| direction).unwrap_or(Direction::Left)
+    }
+}
+
+impl Player {
+    /// Returns the `Player` selected using `--agent` and its options. Without `--agent`, a weight
+    /// file passed using `--weights` selects an n-tuple network, anything else the heuristic.
+    /// Exits when the weights cannot be read.
+    fn from(arguments: &ArgMatches) -> Self {
+        let weights = arguments.value_of("weights");
+        let number  = |name, default| arguments.value_of(name).unwrap_or(default).parse::<u32>().unwrap();
+
+        match arguments.value_of("agent") {
+            Some("random")     => Player::Random,
+            Some("greedy")     => Player::Greedy,
+            Some("corner")     => Player::Corner,
+            Some("snake")      => Player::Snake,
+            Some("expectimax") => Player::Expectimax(Arc::new(Heuristic::with_params(&params_from(arguments))), number("depth", "3")),
+            Some("rollout")    => Player::Rollout(number("playouts", "100")),
+            Some("mcts")       => Player::Mcts(match arguments.value_of("time") {
+                Some(time) => Budget::Time(Duration::from_millis(time.parse().unwrap())),
+                None       => Budget::Iterations(number("iterations", "1000"))
+            }),
+            Some("ntuple")     => Player::NTuple(Arc::new(load_network(weights.unwrap_or("ntuple.bin")))),
+            Some(_)            => Player::Heuristic(Arc::new(Heuristic::with_params(&params_from(arguments)))),
+            None               => match weights {
+                Some(weights) if WeightFile::is_weight_file(weights) => Player::NTuple(Arc::new(load_network(weights))),
+                _ => Player::Heuristic(Arc::new(Heuristic::with_params(&params_from(arguments))))
+            }
+        }
+    }
+
+    /// Returns a new agent that plays as this player.
+    fn agent(&self) -> Box<dyn Agent> {
+        match self {
+            Player::Random                       => Box::new(Random::new()),
+            Player::Greedy                       => Box::new(Greedy),
+            Player::Corner                       => Box::new(Corner),
+            Player::Snake                        => Box::new(Snake::default()),
+            Player::Heuristic(heuristic)         => Box::new(OnePly::with_heuristic(heuristic.clone())),
+            Player::Expectimax(heuristic, depth) => {
+                let mut agent = Expectimax::with_depth(*depth);
+
+                agent.set_heuristic(heuristic.clone());
+                Box::new(agent)
+            },
+            Player::Mcts(budget)                 => Box::new(Mcts::new(*budget)),
+            Player::Rollout(playouts)            => Box::new(MonteCarlo::new(*playouts)),
+            Player::NTuple(network)              => Box::new(SharedNetwork(network.clone()))
+        }
+    }
+
+    /// Returns a description of this player and its settings.
+    fn describe(&self) -> String {
+        let weights = |heuristic: &Heuristic| heuristic.params.to_string().trim().replace('\n', ", ");
+
+        match self {
+            Player::Random                       => String::from("agent: random"),
+            Player::Greedy                       => String::from("agent: greedy"),
+            Player::Corner                       => String::from("agent: corner"),
+            Player::Snake                        => String::from("agent: snake"),
+            Player::Heuristic(heuristic)         => format!("agent: heuristic, weights: {}", weights(heuristic)),
+            Player::Expectimax(heuristic, depth) => format!("agent: expectimax, depth: {}, weights: {}", depth, weights(heuristic)),
+            Player::Mcts(Budget::Iterations(n))  => format!("agent: mcts, iterations: {}", n),
+            Player::Mcts(Budget::Time(time))     => format!("agent: mcts, time-ms: {}", time.as_millis()),
+            Player::Rollout(playouts)            => format!("agent: rollout, playouts: {}", playouts),
+            Player::NTuple(network)              => format!("agent: ntuple, patterns: {}", network.patterns().len())
+        }
+    }
+}
+
+/// Returns the n-tuple network saved at `path`. Exits when the network cannot be read.
+fn load_network(path: &str) -> NTupleNetwork {
+    NTupleNetwork::load(path).unwrap_or_else(|err| {
+        eprintln!("error: {}: {}", path, err);
+        exit(1);
+    })
+}
+
+/// Returns the `HeuristicParams` from `weights`, which is either a path to a TOML / JSON file or
+/// a list of `key=value` pairs. Anything without a `=` or `:` separator is read as a path, so a
+/// mistyped path reports the missing file. Exits when the weights cannot be read.
+fn load_params(weights: &str) -> HeuristicParams {
+    let params = if Path::new(weights).is_file() || !weights.contains(&['=', ':'][..]) {
+        HeuristicParams::from_file(weights)
+    } else {
+        HeuristicParams::parse(weights)
+    };
+
+    params.unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        exit(1);
+    })
+}
+
+/// Validates that `val` is a number larger than `0`.
+fn positive(val: String) -> Result<(), String> {
+    match val.parse::<i32>() {
+        Ok(n) if n > 0 => Ok(()),
+        Ok(_)          => Err(String::from("value must be >= 1")),
+        Err(_)         => Err(String::from("value must be a number!"))
+    }
+}
+
+/// Validates that `val` is a number larger than `0`, fractions allowed.
+fn rate(val: String) -> Result<(), String> {
+    match val.parse::<f32>() {
+        Ok(n) if n > 0.0 => Ok(()),
+        Ok(_)            => Err(String::from("value must be > 0")),
+        Err(_)           => Err(String::from("value must be a number!"))
+    }
+}
+
+/// Validates that `val` is a number.
+fn number(val: String) -> Result<(), String> {
+    match val.parse::<u64>() {
+        Ok(_)  => Ok(()),
+        Err(_) => Err(String::from("value must be a number!"))
+    }
+}
+
+fn main() {
+    let arguments = clap_app!(app =>
+                        (name: "2048.rs")
+                        (version: "0.1.0")
+                        (author: "Sidney Liebrand <sidneyliebrand@gmail.com>")
+                        (about: "2048 implemented using bit shifting based on github user nneonneo's c++ implementation")
+                        (@arg quiet: -q --quiet "don't print output")
+                        (@arg count: -c --count [COUNT] +takes_value {positive} "set the number of games played\n<COUNT> default: 1, min: 1\n ")
+                        (@arg threads: -t --threads [THREADS] +takes_value {positive} "[<COUNT>/<THREADS>] games played per thread\n<THREADS> default: 1, min: 1\n ")
+                        (@arg weights: -w --weights [WEIGHTS] +takes_value "heuristic weights, either a TOML / JSON file or a list\ne.g. merge_weight=700,sum_power=3.5\nor an n-tuple network weight file saved by `train`\n ")
+                        (@arg agent: -a --agent [AGENT] +takes_value possible_value[random greedy corner snake heuristic expectimax mcts rollout ntuple] "agent used to play games\n<AGENT> default: heuristic, or ntuple for a weight file\n ")
+                        (@arg depth: -d --depth [DEPTH] +takes_value {positive} "expectimax search depth\n<DEPTH> default: 3\n ")
+                        (@arg iterations: -i --iterations [ITERATIONS] +takes_value {positive} "mcts iterations per move\n<ITERATIONS> default: 1000\n ")
+                        (@arg time: --("time-ms") [TIME] +takes_value {positive} conflicts_with[iterations] "mcts time per move in milliseconds, instead of iterations\n ")
+                        (@arg playouts: -p --playouts [PLAYOUTS] +takes_value {positive} "rollout playouts per legal move\n<PLAYOUTS> default: 100\n ")
+                        (@arg record: -r --record [DIR] +takes_value "write a replay of every game to <DIR>\n ")
+                        (@arg seed: -s --seed [SEED] +takes_value {number} "master seed, every game is seeded from it and its index\nso results do not depend on <THREADS>\n ")
+                        (@arg game: -g --game [INDEX] +takes_value {number} requires[seed count] "only play the game with index <INDEX> of the seeded batch\nof <COUNT> games, <INDEX> must be lower than <COUNT>\n ")
+                        (@subcommand replay =>
+                            (about: "replay recorded games and check them against their final board")
+                            (@arg files: +required +multiple "replay files to check")
+                            (@arg view: -v --view "show the game board by board in the terminal instead")
+                            (@arg delay: -d --delay [DELAY] +takes_value {positive} "milliseconds per move when playing with --view\n<DELAY> default: 300\n ")
+                        )
+                        (@subcommand play =>
+                            (about: "play the game yourself in the terminal")
+                            (@arg depth: -d --depth [DEPTH] +takes_value {positive} "expectimax search depth used for hints and auto-play\n<DEPTH> default: 2\n ")
+                            (@arg moves: -n --moves [MOVES] +takes_value {positive} "number of moves played per auto-play key press\n<MOVES> default: 10\n ")
+                            (@arg weights: -w --weights [WEIGHTS] +takes_value "heuristic weights used for hints and auto-play\n ")
+                            (@arg record: -r --record [FILE] +takes_value "write a replay of the current game to <FILE> when quitting\n ")
+                            (@arg resume: --resume "continue the game that was saved when quitting")
+                        )
+                        (@subcommand stats =>
+                            (about: "show high scores and statistics of finished interactive games")
+                            (@arg top: -n --top [TOP] +takes_value {positive} "number of high scores shown\n<TOP> default: 10\n ")
+                        )
+                        (@subcommand tune =>
+                            (about: "tune the heuristic weights using a genetic algorithm")
+                            (@arg generations: -g --generations [GENERATIONS] +takes_value {positive} "number of generations\n<GENERATIONS> default: 20\n ")
+                            (@arg population: -p --population [POPULATION] +takes_value {positive} "number of candidates per generation\n<POPULATION> default: 16, min: 4\n ")
+                            (@arg games: -n --games [GAMES] +takes_value {positive} "number of seeded games played per candidate\n<GAMES> default: 20\n ")
+                            (@arg depth: -d --depth [DEPTH] +takes_value {positive} "expectimax search depth used to play games\n<DEPTH> default: 1\n ")
+                            (@arg seed: -s --seed [SEED] +takes_value {number} "seed for the games and the optimiser\n<SEED> default: 0\n ")
+                            (@arg threads: -t --threads [THREADS] +takes_value {positive} "number of threads used to evaluate candidates\n<THREADS> default: 1\n ")
+                            (@arg weights: -w --weights [WEIGHTS] +takes_value "starting weights, either a TOML / JSON file or a list\n ")
+                            (@arg output: -o --output [OUTPUT] +takes_value "file the best weights are written to\n<OUTPUT> default: weights.toml\n ")
+                            (@arg log: -l --log [LOG] +takes_value "file the per-generation log is written to\n<LOG> default: tune.log\n ")
+                        )
+                        (@subcommand train =>
+                            (about: "train an n-tuple network using temporal difference learning")
+                            (@arg episodes: -e --episodes [EPISODES] +takes_value {positive} "number of self-play games\n<EPISODES> default: 10000\n ")
+                            (@arg alpha: -a --alpha [ALPHA] +takes_value {rate} "learning rate\n<ALPHA> default: 0.1\n ")
+                            (@arg patterns: -p --patterns [PATTERNS] +takes_value possible_value[small large] "tuple patterns to train\n<PATTERNS> default: small\n ")
+                            (@arg seed: -s --seed [SEED] +takes_value {number} "seed of the first game, incremented per game\n<SEED> default: 0\n ")
+                            (@arg report: -r --report [REPORT] +takes_value {positive} "print the average score and save the weights every <REPORT> games\n<REPORT> default: 1000\n ")
+                            (@arg output: -o --output [OUTPUT] +takes_value "file the trained weights are written to\n<OUTPUT> default: ntuple.bin\n ")
+                        )
+                    ).get_matches();
+
+    match arguments.subcommand() {
+        ("play", Some(play_arguments))     => play::run(play_arguments),
+        ("replay", Some(replay_arguments)) => replay::run(replay_arguments),
+        ("stats", Some(stats_arguments))   => stats::run(stats_arguments),
+        ("tune", Some(tune_arguments))     => tune::run(tune_arguments),
+        ("train", Some(train_arguments))   => train::run(train_arguments),
+        _                                  => simulate(&arguments)
+    }
+}
+
+/// Returns the `HeuristicParams` passed using `--weights` or the defaults.
+fn params_from(arguments: &ArgMatches) -> HeuristicParams {
+    match arguments.value_of("weights") {
+        Some(weights) => load_params(weights),
+        None          => HeuristicParams::default()
+    }
+}
+
+/// Plays `--count` games on `--threads` threads and prints statistics about the results.
+fn simulate(arguments: &ArgMatches) {
+    let verbose = !arguments.is_present("quiet");
+    let count   = arguments.value_of("count").unwrap_or("1").parse::<usize>().unwrap();
+    let threads = arguments.value_of("threads").unwrap_or("1").parse::<usize>().unwrap();
+    let player  = Player::from(arguments);
+    let record  = arguments.value_of("record").map(PathBuf::from);
+
+    if let Some(dir) = &record {
+        fs::create_dir_all(dir).unwrap_or_else(|err| {
+            eprintln!("error: {}: {}", dir.display(), err);
+            exit(1);
+        });
+    }
+
+    let seed   = arguments.value_of("seed").map(|seed| seed.parse::<u64>().unwrap());
+    let config = BatchConfig { count, threads, record: record.is_some(), seed, ..BatchConfig::default() };
+
+    if let Some(index) = arguments.value_of("game") {
+        let index = index.parse::<usize>().unwrap();
+
+        if index >= count {
+            eprintln!("error: --game {} is not part of a batch of {} games, indices start at 0", index, count);
+            exit(1);
+        }
+
+        return rerun(&config, index, &player, record.as_deref(), verbose);
+    }
+
+    let report = run_batch(&config, || player.agent());
+
+    if let Some(dir) = &record {
+        for game in &report.games {
+            let path = dir.join(format!("game-{}.replay", game.index));
+
+            if let Some(Err(err)) = game.replay.as_ref().map(|replay| replay.save(&path)) {
+                eprintln!("error: {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    if verbose {
+        let best = report.best().unwrap();
+
+        println!("count: {}, threads: {}", count, threads);
+        if let Some(seed) = seed { println!("seed: {}", seed) }
+        println!("{}", player.describe());
+        println!("played: {}", report.games.len());
+        println!("best_idx: {}", best.index);
+        println!("average score: {}", report.average_score());
+        println!("best board: {}", best.score);
+
+        print_board(best.board);
+
+        print_tile_table(&report.games.iter().map(|game| game.highest).collect::<Vec<u64>>());
+        println!();
+    }
+}
+
+/// Plays only game `index` of the batch described by `config` and prints its result.
+/// With a seed, this is the exact same game as the one with that index in a full batch.
+fn rerun(config: &BatchConfig, index: usize, player: &Player, record: Option<&Path>, verbose: bool) {
+    let game = run_game(config, index, &mut player.agent());
+
+    if let (Some(dir), Some(replay)) = (record, &game.replay) {
+        let path = dir.join(format!("game-{}.replay", index));
+
+        if let Err(err) = replay.save(&path) { eprintln!("error: {}: {}", path.display(), err) }
+    }
+
+    if verbose {
+        println!("game: {}, seed: {}", index, game.seed);
+        println!("{}", player.describe());
+        println!("score: {}, moves: {}, highest tile: {}", game.score, game.moves, game.highest);
+
+        print_board(game.board);
+    }
+}
+
+/// Prints `board` as a grid surrounded by empty lines.
+fn print_board(board: u64) {
+    let mut board = board;
+
+    println!();
+    for _ in 0 .. 4 {
+        for _ in 0 .. 4 {
+            let pow = board & 0xF;
+            let val = if pow == 0 { 0 } else { 1 << pow };
+
+            print!("{:5}", val);
+            board >>= 4;
+        }
+        println!();
+    }
+    println!();
+}
+
+/// Prints how many of the games with highest tiles `best_tiles` reached each tile.
+fn print_tile_table(best_tiles: &[u64]) {
+    let end_c = best_tiles.len();
+
+    for n in &[2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384, 32768] {
+        let count = best_tiles.iter().filter(|t| *t >= n).count();
+        let perc  = if count > 0 {  (count as f64 / end_c as f64) * 100.0 } else { 0f64 };
+        println!("{:5}: ({:03.02}%) {} of {}", n, perc, count, end_c);
+    }
+}
@@ -0,0 +1,219 @@
+//! `play` subcommand: play the game yourself in the terminal.
+
+use clap::ArgMatches;
+
+use std::process::exit;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tfe::{Agent, Direction, Game, GameHistory, GameRng, SpawnRules};
+use tfe::ai::Expectimax;
+use tfe::heuristic::Heuristic;
+use tfe::replay::Replay;
+
+use save::{self, SavedGame};
+use stats::{self, GameRecord};
+use terminal::{self, Key, Terminal};
+
+/// What the player asked for using a key press.
+enum Action {
+    Move(Direction),
+    Hint,
+    AutoPlay,
+    Undo,
+    Redo,
+    First,
+    Last,
+    Restart,
+    Quit,
+    Nothing
+}
+
+impl Action {
+    /// Maps arrow keys, WASD and hjkl to moves, `?` to a hint, `p` to auto-play, `u` and `y` to
+    /// undo and redo, `g` and `G` to the first and last move, `r` to restart and `q` or escape
+    /// to quit.
+    fn from(key: Key) -> Self {
+        match key {
+            Key::Up    | Key::Char('w') | Key::Char('k')     => Action::Move(Direction::Up),
+            Key::Down  | Key::Char('s') | Key::Char('j')     => Action::Move(Direction::Down),
+            Key::Left  | Key::Char('a') | Key::Char('h')     => Action::Move(Direction::Left),
+            Key::Right | Key::Char('d') | Key::Char('l')     => Action::Move(Direction::Right),
+            Key::Char('?')                                   => Action::Hint,
+            Key::Char('p')                                   => Action::AutoPlay,
+            Key::Char('u')                                   => Action::Undo,
+            Key::Char('y') | Key::Char('\x12')               => Action::Redo,
+            Key::Char('g')                                   => Action::First,
+            Key::Char('G')                                   => Action::Last,
+            Key::Char('r')                                   => Action::Restart,
+            Key::Char('q') | Key::Escape | Key::Char('\x03') => Action::Quit,
+            _                                                => Action::Nothing
+        }
+    }
+}
+
+/// Returns the screen showing `game`, the best score so far and a status line.
+fn frame(game: &Game<GameRng>, history: &GameHistory, best: u64, status: &str) -> String {
+    format!(
+        "2048\n\nscore: {:<8} best: {:<8} moves: {} / {}\n\n{}\n{}\n\narrows / wasd / hjkl: move, ?: hint, p: auto-play,\nu / y: undo / redo, g / G: first / last move, r: restart, q: quit\n",
+        game.score, best, game.moves, history.len(), terminal::grid(game.board), status
+    )
+}
+
+/// Returns the evaluation of every direction on `board` by `ai`, the best move is marked.
+fn hint(ai: &mut Expectimax, board: u64) -> String {
+    let scores = ai.scores(board);
+    let best   = scores.iter().cloned().fold(None, |best: Option<(Direction, f64)>, score| match best {
+        Some(b) if b.1 >= score.1 => Some(b),
+        _                         => Some(score)
+    });
+
+    [Direction::Left, Direction::Right, Direction::Up, Direction::Down].iter().map(|&direction| {
+        let name = format!("{:?}", direction).to_lowercase();
+
+        match scores.iter().find(|score| score.0 == direction) {
+            Some(&(_, score)) if best.is_some_and(|b| b.0 == direction) => format!("*{}: {:.0}", name, score),
+            Some(&(_, score))                                           => format!("{}: {:.0}", name, score),
+            None                                                        => format!("{}: -", name)
+        }
+    }).collect::<Vec<String>>().join("  ")
+}
+
+/// Returns a new game seeded with a random seed, so that its spawns can be saved and resumed.
+fn new_game() -> Game<GameRng> {
+    Game::with_rng(GameRng::new(rand::random()))
+}
+
+/// Appends the finished `game`, played for `duration` seconds, to the statistics file.
+/// Returns the status line to show.
+fn finish(game: &Game<GameRng>, duration: u64) -> String {
+    let record = GameRecord {
+        date:     SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        score:    game.score,
+        highest:  Game::highest_tile(game.board),
+        moves:    game.moves,
+        duration
+    };
+
+    match stats::record(&record) {
+        Ok(())   => String::from("game over! press r to play again"),
+        Err(err) => format!("game over! could not save statistics: {}", err)
+    }
+}
+
+/// Runs the interactive game until the player quits. With `--resume`, continues the game that was
+/// saved when the player last quit. The game is saved again when quitting.
+pub fn run(arguments: &ArgMatches) {
+    let depth = arguments.value_of("depth").unwrap_or("2").parse::<u32>().unwrap();
+    let moves = arguments.value_of("moves").unwrap_or("10").parse::<u32>().unwrap();
+    let mut ai = Expectimax::with_depth(depth);
+
+    ai.set_heuristic(Arc::new(Heuristic::with_params(&super::params_from(arguments))));
+
+    let path    = save::save_path();
+    let session = if arguments.is_present("resume") {
+        SavedGame::load(&path).unwrap_or_else(|err| {
+            eprintln!("error: {}: {}", path.display(), err);
+            exit(1);
+        })
+    } else {
+        SavedGame { game: new_game(), history: GameHistory::new(), best: 0, elapsed: 0 }
+    };
+
+    let mut term = Terminal::raw().unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        exit(1);
+    });
+
+    let SavedGame { mut game, mut history, mut best, mut elapsed } = session;
+    let mut started  = Instant::now();
+    let mut recorded = Game::is_over(game.board);
+    let mut status   = String::new();
+
+    // the best score includes every game recorded in the statistics, not just this session
+    best = stats::load().unwrap_or_default().iter().map(|record| record.score).fold(best, u64::max);
+
+    // drawing errors end the session like reading errors do, so that the game is still saved
+    'session: loop {
+        best = best.max(game.score);
+
+        if term.draw(&frame(&game, &history, best, &status)).is_err() { break }
+
+        let key = match term.read_key() {
+            Ok(key) => key,
+            Err(_)  => break
+        };
+
+        status.clear();
+
+        match Action::from(key) {
+            Action::Move(direction) => {
+                if Game::is_over(game.board) {
+                    status = String::from("game over! press r to play again");
+                } else if !history.step(&mut game, &direction).moved {
+                    status = format!("can't move {:?}", direction).to_lowercase();
+                }
+            },
+            Action::Hint     => status = format!("hint: {}", hint(&mut ai, game.board)),
+            Action::AutoPlay => {
+                for _ in 0 .. moves {
+                    if Game::is_over(game.board) { break }
+
+                    let direction = ai.choose(&game.state());
+                    history.step(&mut game, &direction);
+
+                    best = best.max(game.score);
+
+                    let playing = format!("auto-play: {:?}", direction).to_lowercase();
+                    if term.draw(&frame(&game, &history, best, &playing)).is_err() { break 'session }
+
+                    sleep(Duration::from_millis(60));
+                }
+            },
+            Action::Undo     => if !history.undo(&mut game) { status = String::from("nothing to undo") },
+            Action::Redo     => if !history.redo(&mut game) { status = String::from("nothing to redo") },
+            Action::First    => { history.jump_to(&mut game, 0); },
+            Action::Last     => { history.jump_to(&mut game, history.len()); },
+            Action::Restart  => {
+                game     = new_game();
+                history  = GameHistory::new();
+                elapsed  = 0;
+                started  = Instant::now();
+                recorded = false;
+            },
+            Action::Quit     => break,
+            Action::Nothing  => ()
+        }
+
+        // a game is recorded once until it is restarted, undoing and finishing it again does not
+        // record it a second time
+        if Game::is_over(game.board) {
+            if !recorded {
+                recorded = true;
+                status   = finish(&game, elapsed + started.elapsed().as_secs());
+            } else if status.is_empty() {
+                status = String::from("game over! press r to play again");
+            }
+        }
+    }
+
+    drop(term);
+
+    if let Some(path) = arguments.value_of("record") {
+        let start  = history.entries().first().map(|entry| entry.board).unwrap_or(game.board);
+        let replay = Replay::from_history(start, SpawnRules::default(), &history);
+
+        match replay.save(path) {
+            Ok(())   => println!("replay written to {}", path),
+            Err(err) => eprintln!("error: {}: {}", path, err)
+        }
+    }
+
+    elapsed += started.elapsed().as_secs();
+
+    match (SavedGame { game, history, best, elapsed }).save(&path) {
+        Ok(())   => println!("game saved, continue using `2048 play --resume`"),
+        Err(err) => eprintln!("error: {}: {}", path.display(), err)
+    }
+}
@@ -0,0 +1,58 @@
+//! `replay` subcommand: rebuilds recorded games and checks them against their final board.
+
+use clap::ArgMatches;
+
+use std::process::exit;
+
+use tfe::replay::Replay;
+
+use view;
+
+/// Verifies every replay file passed to the subcommand, exits with `1` if any of them fails.
+/// With `--view`, shows the game in the terminal instead.
+pub fn run(arguments: &ArgMatches) {
+    if arguments.is_present("view") { return view(arguments) }
+
+    let mut failed = 0;
+
+    for path in arguments.values_of("files").unwrap() {
+        let result = Replay::load(path)
+            .map_err(|err| err.to_string())
+            .and_then(|replay| replay.verify().map(|_| replay));
+
+        match result {
+            Ok(replay) => {
+                let spawns = match replay.seed {
+                    Some(seed) => format!("seed: {}", seed),
+                    None       => String::from("explicit spawns")
+                };
+
+                println!("{}: ok, moves: {}, score: {}, {}", path, replay.moves.len(), replay.score, spawns);
+            },
+            Err(err) => {
+                println!("{}: failed, {}", path, err);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 { exit(1) }
+}
+
+/// Shows the single replay file passed to the subcommand in the terminal.
+fn view(arguments: &ArgMatches) {
+    let files: Vec<&str> = arguments.values_of("files").unwrap().collect();
+    let delay            = arguments.value_of("delay").unwrap_or("300").parse::<u64>().unwrap();
+
+    if files.len() != 1 {
+        eprintln!("error: --view shows a single replay file");
+        exit(1);
+    }
+
+    let replay = Replay::load(files[0]).unwrap_or_else(|err| {
+        eprintln!("error: {}: {}", files[0], err);
+        exit(1);
+    });
+
+    view::run(&replay, delay);
+}
@@ -0,0 +1,218 @@
+//! Saved interactive games, stored in the data directory so that they can be resumed later.
+//!
+//! All numbers are stored little-endian:
+//!
+//! | field    | type      | description                                       |
+//! |----------|-----------|---------------------------------------------------|
+//! | magic    | `[u8; 4]` | always `TFES`                                     |
+//! | version  | `u16`     | format version, currently `1`                     |
+//! | board    | `u64`     | the current board                                 |
+//! | score    | `u64`     | the current score                                 |
+//! | moves    | `u64`     | the current move count                            |
+//! | fours    | `u64`     | the number of spawned fours                       |
+//! | rng      | `u64`     | state of the `GameRng` that spawns the next tiles |
+//! | best     | `u64`     | best score of the session                         |
+//! | elapsed  | `u64`     | seconds played in this game                       |
+//! | position | `u32`     | number of history entries that are applied        |
+//! | entries  | `u32`     | number of history entries that follow             |
+//! | entry    |           | board `u64`, move `u8`, spawn count `u8`, spawns  |
+//! |          |           | `[u8]` as `index << 4 \| power`, score `u64` and  |
+//! |          |           | the `GameRng` state before and after the move as  |
+//! |          |           | two `u64`s                                        |
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use tfe::{Direction, Game, GameHistory, GameRng, HistoryEntry, Spawn, SpawnRules};
+use tfe::io::{invalid, read_bytes};
+
+/// The first 4 bytes of every save file.
+const MAGIC: &[u8; 4] = b"TFES";
+
+/// The version of the format written by this binary.
+const VERSION: u16 = 1;
+
+/// Returns the directory the binary stores its data in, `$XDG_DATA_HOME/tfe` or
+/// `~/.local/share/tfe` when `XDG_DATA_HOME` is not set.
+pub fn data_dir() -> PathBuf {
+    match env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("tfe"),
+        None      => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(".local/share/tfe")
+    }
+}
+
+/// Returns the path of the saved interactive game.
+pub fn save_path() -> PathBuf {
+    data_dir().join("game.save")
+}
+
+/// Reads a little-endian `u64` from `reader`.
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(read_bytes(reader)?))
+}
+
+/// An interactive game along with its history and the best score of the session.
+pub struct SavedGame {
+    pub game:    Game<GameRng>,
+    pub history: GameHistory,
+    pub best:    u64,
+    pub elapsed: u64
+}
+
+impl SavedGame {
+    /// Writes this game to `path`, creating its directory when needed.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() { fs::create_dir_all(dir)? }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+
+        for value in &[self.game.board, self.game.score, self.game.moves, self.game.fours, self.game.rng().state(), self.best, self.elapsed] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+
+        writer.write_all(&(self.history.position() as u32).to_le_bytes())?;
+        writer.write_all(&(self.history.len() as u32).to_le_bytes())?;
+
+        for entry in self.history.entries() {
+            writer.write_all(&entry.board.to_le_bytes())?;
+            writer.write_all(&[entry.direction.code(), entry.spawns.len() as u8])?;
+
+            for spawn in &entry.spawns {
+                writer.write_all(&[((spawn.index as u8) << 4) | spawn.value as u8])?;
+            }
+
+            writer.write_all(&entry.score.to_le_bytes())?;
+            writer.write_all(&entry.rng_before.state().to_le_bytes())?;
+            writer.write_all(&entry.rng_after.state().to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a game saved at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        if &read_bytes::<_, 4>(&mut reader)? != MAGIC { return Err(invalid("not a saved game")) }
+
+        let version = u16::from_le_bytes(read_bytes(&mut reader)?);
+
+        if version != VERSION {
+            return Err(invalid(&format!("unsupported save version: {}", version)));
+        }
+
+        let board    = read_u64(&mut reader)?;
+        let score    = read_u64(&mut reader)?;
+        let moves    = read_u64(&mut reader)?;
+        let fours    = read_u64(&mut reader)?;
+        let rng      = read_u64(&mut reader)?;
+        let best     = read_u64(&mut reader)?;
+        let elapsed  = read_u64(&mut reader)?;
+        let position = u32::from_le_bytes(read_bytes(&mut reader)?) as usize;
+        let count    = u32::from_le_bytes(read_bytes(&mut reader)?) as usize;
+        let mut entries = vec![];
+
+        for _ in 0 .. count {
+            let board            = read_u64(&mut reader)?;
+            let [direction, len] = read_bytes::<_, 2>(&mut reader)?;
+            let direction        = Direction::from_code(direction).ok_or_else(|| invalid("invalid move in history"))?;
+            let mut spawns       = vec![0; len as usize];
+
+            reader.read_exact(&mut spawns)?;
+
+            let score  = read_u64(&mut reader)?;
+            let before = read_u64(&mut reader)?;
+            let after  = read_u64(&mut reader)?;
+
+            entries.push(HistoryEntry {
+                board,
+                direction,
+                spawns:     spawns.iter().map(|&b| Spawn { index: (b >> 4) as usize, value: (b & 0xF) as u64 }).collect(),
+                score,
+                rng_before: GameRng::new(before),
+                rng_after:  GameRng::new(after)
+            });
+        }
+
+        let mut game = Game::from_board_with(board, GameRng::new(rng), SpawnRules::default());
+
+        game.score = score;
+        game.moves = moves;
+        game.fours = fours;
+
+        Ok(SavedGame { game, history: GameHistory::with_entries(entries, position), best, elapsed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+
+    use tfe::{Direction, Game, GameHistory, GameRng};
+
+    use super::{SavedGame, MAGIC, VERSION};
+
+    /// Returns a path in the temporary directory that is unique to this process and `name`.
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("tfe-save-test-{}-{}", process::id(), name))
+    }
+
+    #[test]
+    fn round_trips_a_game_with_history() {
+        let path        = temp_path("round-trip");
+        let mut game    = Game::with_rng(GameRng::new(42));
+        let mut history = GameHistory::new();
+
+        for direction in [Direction::Left, Direction::Up, Direction::Right, Direction::Down].iter().cycle().take(12) {
+            history.step(&mut game, direction);
+        }
+
+        history.undo(&mut game);
+
+        let board = game.board;
+        let redo  = history.entries()[history.position()].clone();
+
+        (SavedGame { game, history, best: 1234, elapsed: 56 }).save(&path).unwrap();
+
+        let SavedGame { mut game, mut history, best, elapsed } = SavedGame::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!((game.board, best, elapsed), (board, 1234, 56));
+        assert_eq!(history.entries()[history.position()], redo);
+
+        // undoing after loading restores the generator, the same move spawns the same tiles again
+        let last = history.entries()[history.position() - 1].clone();
+
+        assert!(history.undo(&mut game));
+        assert_eq!(game.board, last.board);
+
+        history.step(&mut game, &last.direction);
+        assert_eq!(game.board, last.after());
+    }
+
+    #[test]
+    fn rejects_truncated_saves_with_huge_entry_counts() {
+        let path      = temp_path("truncated");
+        let mut bytes = MAGIC.to_vec();
+
+        bytes.extend(&VERSION.to_le_bytes());
+        bytes.extend(&[0; 7 * 8]);
+        bytes.extend(&0u32.to_le_bytes());
+        bytes.extend(&u32::MAX.to_le_bytes());
+
+        fs::write(&path, &bytes).unwrap();
+
+        let result = SavedGame::load(&path);
+        fs::remove_file(&path).ok();
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! High scores and statistics of finished interactive games.
+//!
+//! Every finished game is appended as a line of tab separated values to `stats.tsv` in the data
+//! directory: the date as seconds since the unix epoch, the score, the highest tile, the number of
+//! moves and the duration in seconds.
+
+use clap::ArgMatches;
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::exit;
+
+use save;
+
+/// A single finished game.
+pub struct GameRecord {
+    pub date:     u64,
+    pub score:    u64,
+    pub highest:  u64,
+    pub moves:    u64,
+    pub duration: u64
+}
+
+/// Returns the path of the statistics file.
+pub fn stats_path() -> PathBuf {
+    save::data_dir().join("stats.tsv")
+}
+
+/// Appends `record` to the statistics file, creating it when needed.
+pub fn record(record: &GameRecord) -> io::Result<()> {
+    let path = stats_path();
+
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir)? }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    writeln!(file, "{}\t{}\t{}\t{}\t{}", record.date, record.score, record.highest, record.moves, record.duration)
+}
+
+/// Reads every record from the statistics file, no file means no games were finished yet.
+pub fn load() -> io::Result<Vec<GameRecord>> {
+    let contents = match fs::read_to_string(stats_path()) {
+        Ok(contents)                                       => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err)                                           => return Err(err)
+    };
+
+    contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(i, line)| {
+        let fields: Vec<u64> = line.split('\t').map(|field| field.trim().parse::<u64>()).collect::<Result<_, _>>()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid record on line {}", i + 1)))?;
+
+        match fields[..] {
+            [date, score, highest, moves, duration] => Ok(GameRecord { date, score, highest, moves, duration }),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid record on line {}", i + 1)))
+        }
+    }).collect()
+}
+
+/// Returns `seconds` since the unix epoch as a `YYYY-MM-DD` date.
+fn date(seconds: u64) -> String {
+    // days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days  = (seconds / 86400) as i64 + 719_468;
+    let era   = days.div_euclid(146_097);
+    let doe   = days - era * 146_097;
+    let yoe   = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy   = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp    = (5 * doy + 2) / 153;
+    let day   = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year  = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Prints the high scores, averages and tile-percentage table of all finished games.
+pub fn run(arguments: &ArgMatches) {
+    let top     = arguments.value_of("top").unwrap_or("10").parse::<usize>().unwrap();
+    let records = load().unwrap_or_else(|err| {
+        eprintln!("error: {}: {}", stats_path().display(), err);
+        exit(1);
+    });
+
+    if records.is_empty() {
+        println!("no finished games yet, play one using `2048 play`");
+        return;
+    }
+
+    let played = records.len() as f64;
+    let mut best: Vec<&GameRecord> = records.iter().collect();
+
+    best.sort_by_key(|record| std::cmp::Reverse(record.score));
+
+    println!("played: {}", records.len());
+    println!("average score: {:.1}", records.iter().map(|r| r.score).sum::<u64>() as f64 / played);
+    println!("average moves: {:.1}", records.iter().map(|r| r.moves).sum::<u64>() as f64 / played);
+    println!("average duration: {:.0}s", records.iter().map(|r| r.duration).sum::<u64>() as f64 / played);
+    println!();
+    println!("  #  score    tile   moves  duration  date");
+
+    for (i, record) in best.iter().take(top).enumerate() {
+        println!("{:3}  {:<8} {:<6} {:<6} {:>7}s  {}", i + 1, record.score, record.highest, record.moves, record.duration, date(record.date));
+    }
+
+    println!();
+    super::print_tile_table(&records.iter().map(|r| r.highest).collect::<Vec<u64>>());
+    println!();
+}
@@ -0,0 +1,245 @@
+//! Minimal full-screen terminal support using raw mode, for the interactive subcommands.
+
+use libc;
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::time::Duration;
+
+/// A key press read from the terminal.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Escape,
+    Char(char)
+}
+
+/// How long to wait for the rest of an escape sequence before a lone `ESC` is read as
+/// `Key::Escape`. Sequences can be split over several reads, for example over ssh.
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Puts the terminal in raw mode on the alternate screen while it is alive. The original terminal
+/// settings are restored when it is dropped.
+pub struct Terminal {
+    original: libc::termios,
+    pending:  VecDeque<u8>
+}
+
+impl Terminal {
+    /// Switches the terminal to raw mode. Fails when stdin is not a terminal.
+    pub fn raw() -> io::Result<Self> {
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) == 0 {
+                return Err(io::Error::other("stdin is not a terminal"));
+            }
+
+            let mut original: libc::termios = mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 { return Err(io::Error::last_os_error()) }
+
+            let mut raw = original;
+            libc::cfmakeraw(&mut raw);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 { return Err(io::Error::last_os_error()) }
+
+            print!("\x1b[?1049h\x1b[?25l");
+            io::stdout().flush()?;
+
+            Ok(Terminal { original, pending: VecDeque::new() })
+        }
+    }
+
+    /// Blocks until a key is pressed and returns it. Unknown escape sequences, such as the ones
+    /// sent by function keys or Alt combinations, are skipped. When several keys arrive at once,
+    /// for example when pasting or holding down an arrow key, they are returned one by one.
+    pub fn read_key(&mut self) -> io::Result<Key> {
+        loop {
+            if let Some(key) = self.next_key()? { return Ok(key) }
+            if self.fill()? == 0 { return Ok(Key::Escape) }
+        }
+    }
+
+    /// Waits at most `timeout` for a key to be pressed and returns it, `None` if no key was
+    /// pressed in time.
+    pub fn poll_key(&mut self, timeout: Duration) -> io::Result<Option<Key>> {
+        if let Some(key) = self.next_key()? { return Ok(Some(key)) }
+        if !wait(timeout)? { return Ok(None) }
+        if self.fill()? == 0 { return Ok(Some(Key::Escape)) }
+
+        self.next_key()
+    }
+
+    /// Reads the bytes that are available on stdin into the pending bytes and returns how many
+    /// were read, `0` when stdin was closed.
+    fn fill(&mut self) -> io::Result<usize> {
+        let mut buffer = [0u8; 64];
+        let read       = io::stdin().read(&mut buffer)?;
+
+        self.pending.extend(&buffer[.. read]);
+        Ok(read)
+    }
+
+    /// Removes the first whole key from the pending bytes and returns it, skipping unknown escape
+    /// sequences. An escape sequence that was only partly read is completed by waiting at most
+    /// `ESCAPE_TIMEOUT` for the rest of it, if it does not arrive its `ESC` is read as
+    /// `Key::Escape`. Returns `None` when no key is pending.
+    fn next_key(&mut self) -> io::Result<Option<Key>> {
+        loop {
+            match parse_key(self.pending.make_contiguous()) {
+                Some((key, len)) => {
+                    self.pending.drain(.. len);
+                    if key.is_some() { return Ok(key) }
+                },
+                None if self.pending.is_empty() => return Ok(None),
+                None => if !wait(ESCAPE_TIMEOUT)? || self.fill()? == 0 {
+                    self.pending.pop_front();
+                    return Ok(Some(Key::Escape));
+                }
+            }
+        }
+    }
+
+    /// Clears the screen and draws `frame`.
+    pub fn draw(&mut self, frame: &str) -> io::Result<()> {
+        let mut stdout = io::stdout();
+
+        write!(stdout, "\x1b[H\x1b[2J{}", frame.replace('\n', "\r\n"))?;
+        stdout.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original); }
+
+        print!("\x1b[?25h\x1b[?1049l");
+        io::stdout().flush().ok();
+    }
+}
+
+/// Waits at most `timeout` for input on stdin, returns `false` if none arrived in time.
+fn wait(timeout: Duration) -> io::Result<bool> {
+    let mut fd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    let ready  = unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) };
+
+    if ready < 0 { Err(io::Error::last_os_error()) } else { Ok(ready > 0) }
+}
+
+/// Parses the key at the start of `bytes` and returns it with the number of bytes it takes up.
+/// The key is `None` for unknown escape sequences and Alt combinations. Returns `None` when
+/// `bytes` does not start with a whole key yet, which includes a lone `ESC`.
+fn parse_key(bytes: &[u8]) -> Option<(Option<Key>, usize)> {
+    match bytes {
+        [] | [b'\x1b']             => None,
+        [b'\x1b', b'[', rest @ ..] => {
+            // parameter and intermediate bytes followed by a single final byte
+            let params = rest.iter().take_while(|byte| (0x20 ..= 0x3F).contains(*byte)).count();
+            let last   = *rest.get(params)?;
+
+            Some((if params == 0 { arrow(last) } else { None }, params + 3))
+        },
+        [b'\x1b', b'O', rest @ ..] => Some((arrow(*rest.first()?), 3)),
+        [b'\x1b', b'\x1b', ..]     => Some((Some(Key::Escape), 1)),
+        [b'\x1b', _, ..]           => Some((None, 2)),
+        [byte, ..]                 => Some((Some(Key::Char(*byte as char)), 1))
+    }
+}
+
+/// Returns the arrow key identified by the final byte of an escape sequence.
+fn arrow(byte: u8) -> Option<Key> {
+    match byte {
+        b'A' => Some(Key::Up),
+        b'B' => Some(Key::Down),
+        b'C' => Some(Key::Right),
+        b'D' => Some(Key::Left),
+        _    => None
+    }
+}
+
+/// Background colors of tiles by power, larger tiles share the last color.
+static COLORS: [u8; 12] = [237, 230, 223, 215, 209, 203, 196, 229, 228, 227, 226, 220];
+
+/// Renders `board` as a colored 4x4 grid. Cell 15 is drawn in the top left corner so that every
+/// move slides tiles in the direction it is named after.
+pub fn grid(board: u64) -> String {
+    highlighted_grid(board, 0, 0)
+}
+
+/// Like `grid` but draws the cells set in the `spawned` and `merged` bit masks, bit `i` for
+/// cell `i`, in reverse video and bold underlined respectively.
+pub fn highlighted_grid(board: u64, spawned: u16, merged: u16) -> String {
+    let mut out = String::from("+------+------+------+------+\n");
+
+    for row in (0 .. 4).rev() {
+        out.push('|');
+
+        for col in (0 .. 4).rev() {
+            let cell  = row * 4 + col;
+            let power = (board >> (cell * 4)) & 0xF;
+            let color = COLORS[(power as usize).min(COLORS.len() - 1)];
+            let label = if power == 0 { String::new() } else { (1u64 << power).to_string() };
+            let style = if spawned >> cell & 1 == 1 {
+                "\x1b[7m"
+            } else if merged >> cell & 1 == 1 {
+                "\x1b[1;4m"
+            } else {
+                ""
+            };
+
+            out.push_str(&format!("\x1b[48;5;{}m\x1b[38;5;235m{}{:^6}\x1b[0m|", color, style, label));
+        }
+
+        out.push_str("\n+------+------+------+------+\n");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_key, Key};
+
+    /// Returns every key parsed from `bytes` and the number of bytes left over.
+    fn keys(mut bytes: &[u8]) -> (Vec<Key>, usize) {
+        let mut keys = vec![];
+
+        while let Some((key, len)) = parse_key(bytes) {
+            keys.extend(key);
+            bytes = &bytes[len ..];
+        }
+
+        (keys, bytes.len())
+    }
+
+    #[test]
+    fn parses_arrows_and_characters() {
+        assert_eq!(keys(b"\x1b[A\x1bOBq"), (vec![Key::Up, Key::Down, Key::Char('q')], 0));
+    }
+
+    #[test]
+    fn parses_repeated_arrows_in_one_read() {
+        assert_eq!(keys(b"\x1b[D\x1b[D\x1b[C"), (vec![Key::Left, Key::Left, Key::Right], 0));
+    }
+
+    #[test]
+    fn waits_for_split_sequences() {
+        assert_eq!(keys(b"\x1b"), (vec![], 1));
+        assert_eq!(keys(b"\x1b["), (vec![], 2));
+        assert_eq!(keys(b"\x1b[1;5"), (vec![], 5));
+        assert_eq!(keys(b"\x1bO"), (vec![], 2));
+        assert_eq!(keys(b"w\x1b[A\x1b"), (vec![Key::Char('w'), Key::Up], 1));
+    }
+
+    #[test]
+    fn skips_unknown_sequences() {
+        // home, F5, ctrl + up, F1 and alt + q
+        assert_eq!(keys(b"\x1b[H\x1b[15~\x1b[1;5A\x1bOP\x1bqa"), (vec![Key::Char('a')], 0));
+    }
+
+    #[test]
+    fn parses_escape_followed_by_escape() {
+        assert_eq!(keys(b"\x1b\x1b[A"), (vec![Key::Escape, Key::Up], 0));
+    }
+}
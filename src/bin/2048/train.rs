@@ -0,0 +1,48 @@
+//! Trains an n-tuple network by self-play and reports the average score while doing so.
+//! The weights are saved every time a report is printed, so training can be interrupted.
+
+use clap::ArgMatches;
+
+use std::process::exit;
+
+use tfe::{Game, GameRng};
+use tfe::ntuple::{NTupleNetwork, SMALL_PATTERNS, LARGE_PATTERNS};
+
+/// Runs the `train` subcommand.
+pub fn run(arguments: &ArgMatches) {
+    let episodes = arguments.value_of("episodes").unwrap_or("10000").parse::<u64>().unwrap();
+    let alpha    = arguments.value_of("alpha").unwrap_or("0.1").parse::<f32>().unwrap();
+    let seed     = arguments.value_of("seed").unwrap_or("0").parse::<u64>().unwrap();
+    let report   = arguments.value_of("report").unwrap_or("1000").parse::<u64>().unwrap();
+    let output   = arguments.value_of("output").unwrap_or("ntuple.bin");
+    let patterns = match arguments.value_of("patterns") {
+        Some("large") => &LARGE_PATTERNS[..],
+        _             => &SMALL_PATTERNS[..]
+    };
+
+    let mut network = NTupleNetwork::new(patterns);
+    let mut total   = 0;
+    let mut best    = 0;
+
+    for episode in 0 .. episodes {
+        let mut game = Game::with_rng(GameRng::new(seed.wrapping_add(episode)));
+
+        network.train_episode(&mut game, alpha);
+
+        total += game.score;
+        best   = best.max(game.score);
+
+        if (episode + 1) % report == 0 || episode + 1 == episodes {
+            let played = (episode % report) + 1;
+
+            println!("episodes: {}, average score: {:.1}, best score: {}", episode + 1, total as f64 / played as f64, best);
+            total = 0;
+            best  = 0;
+
+            network.save(output).unwrap_or_else(|err| {
+                eprintln!("error: {}: {}", output, err);
+                exit(1);
+            });
+        }
+    }
+}
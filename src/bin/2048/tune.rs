@@ -0,0 +1,143 @@
+//! Tunes the heuristic weights using a simple genetic algorithm.
+//!
+//! Every candidate plays the same seeded games so that candidates are compared on equal terms.
+//! After each generation the best candidate so far is written to the output file and a line per
+//! generation is appended to the log.
+
+use clap::ArgMatches;
+use rand::Rng;
+
+use tfe::GameRng;
+use tfe::ai::Expectimax;
+use tfe::heuristic::{Heuristic, HeuristicParams};
+use tfe::sim::{run_batch, BatchConfig};
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::process::exit;
+use std::sync::Arc;
+
+use super::params_from;
+
+/// The weights and powers changed by the optimiser.
+static TUNED: [&str; 6] = ["merge_weight", "empty_weight", "sum_weight", "sum_power", "mono_weight", "mono_power"];
+
+/// A set of weights and the average score it reached.
+#[derive(Clone)]
+struct Candidate {
+    params:  HeuristicParams,
+    fitness: f64
+}
+
+/// Returns the average score of `games` seeded games played using `params` on `threads` threads.
+fn evaluate(params: &HeuristicParams, games: usize, depth: u32, seed: u64, threads: usize) -> f64 {
+    let heuristic = Arc::new(Heuristic::with_params(params));
+    let config    = BatchConfig { count: games, threads, seed: Some(seed), ..BatchConfig::default() };
+    let report    = run_batch(&config, || {
+        let mut agent = Expectimax::with_depth(depth);
+
+        agent.set_heuristic(heuristic.clone());
+        agent
+    });
+
+    report.average_score()
+}
+
+/// Returns `params` with every tuned value randomly scaled up or down by up to `strength`.
+fn mutate<R: Rng>(params: &HeuristicParams, strength: f64, rng: &mut R) -> HeuristicParams {
+    let mut mutated = params.clone();
+
+    for name in TUNED.iter() {
+        if let Some(value) = mutated.get_mut(name) {
+            *value = (*value * (1.0 + rng.gen_range(-strength, strength))).max(0.0);
+        }
+    }
+
+    mutated
+}
+
+/// Returns a child that takes every tuned value from either `a` or `b`.
+fn crossover<R: Rng>(a: &HeuristicParams, b: &HeuristicParams, rng: &mut R) -> HeuristicParams {
+    let mut child = a.clone();
+
+    for name in TUNED.iter() {
+        if rng.gen::<bool>() { child.set(name, b.get(name).unwrap_or(0.0)).ok(); }
+    }
+
+    child
+}
+
+/// Returns the fitness of every candidate in `population`, each evaluated on `threads` threads.
+fn evaluate_all(population: &[HeuristicParams], games: usize, depth: u32, seed: u64, threads: usize) -> Vec<f64> {
+    population.iter().map(|params| evaluate(params, games, depth, seed, threads)).collect()
+}
+
+/// Runs the `tune` subcommand.
+pub fn run(arguments: &ArgMatches) {
+    let generations = arguments.value_of("generations").unwrap_or("20").parse::<u64>().unwrap();
+    let size        = arguments.value_of("population").unwrap_or("16").parse::<usize>().unwrap().max(4);
+    let games       = arguments.value_of("games").unwrap_or("20").parse::<usize>().unwrap();
+    let depth       = arguments.value_of("depth").unwrap_or("1").parse::<u32>().unwrap();
+    let seed        = arguments.value_of("seed").unwrap_or("0").parse::<u64>().unwrap();
+    let threads     = arguments.value_of("threads").unwrap_or("1").parse::<usize>().unwrap();
+    let output      = arguments.value_of("output").unwrap_or("weights.toml");
+    let log_path    = arguments.value_of("log").unwrap_or("tune.log");
+
+    let mut rng  = GameRng::new(seed);
+    let start    = params_from(arguments);
+    let elites   = size / 4;
+    let mut log  = File::create(log_path).unwrap_or_else(|err| {
+        eprintln!("error: {}: {}", log_path, err);
+        exit(1);
+    });
+
+    // the first generation consists of the starting weights and mutations of them.
+    let mut population: Vec<HeuristicParams> = (0 .. size)
+        .map(|i| if i == 0 { start.clone() } else { mutate(&start, 0.5, &mut rng) })
+        .collect();
+    let mut best: Option<Candidate> = None;
+
+    for generation in 0 .. generations {
+        let fitness = evaluate_all(&population, games, depth, seed, threads);
+        let average = fitness.iter().sum::<f64>() / fitness.len() as f64;
+        let mut ranked: Vec<Candidate> = population.iter().cloned().zip(fitness)
+            .map(|(params, fitness)| Candidate { params, fitness })
+            .collect();
+
+        ranked.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+
+        if best.as_ref().is_none_or(|b| ranked[0].fitness > b.fitness) {
+            best = Some(ranked[0].clone());
+        }
+
+        let champion = best.as_ref().unwrap();
+        let line     = format!(
+            "generation: {}, best: {:.1}, average: {:.1}, overall best: {:.1}, weights: {}",
+            generation, ranked[0].fitness, average, champion.fitness,
+            ranked[0].params.to_string().trim().replace('\n', ", ")
+        );
+
+        println!("{}", line);
+        writeln!(log, "{}", line).and_then(|_| fs::write(output, champion.params.to_string())).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            exit(1);
+        });
+
+        // the elites survive, the rest of the population is bred from the best half.
+        let parents = &ranked[.. size / 2];
+
+        population = ranked[.. elites].iter().map(|c| c.params.clone()).collect();
+
+        while population.len() < size {
+            let a     = &parents[rng.gen_range(0, parents.len())].params;
+            let b     = &parents[rng.gen_range(0, parents.len())].params;
+            let child = crossover(a, b, &mut rng);
+
+            population.push(mutate(&child, 0.2, &mut rng));
+        }
+    }
+
+    if let Some(champion) = best {
+        println!("best average score: {:.1}, weights written to {}", champion.fitness, output);
+    }
+}
@@ -0,0 +1,207 @@
+//! Terminal viewer that shows a recorded game board by board.
+
+use std::process::exit;
+use std::time::Duration;
+
+use tfe::{Direction, Game};
+use tfe::replay::Replay;
+
+use terminal::{self, Key, Terminal};
+
+/// Returns the cells of each line in the order that a move in `direction` slides tiles towards,
+/// the cell tiles end up in first.
+fn lines(direction: Direction) -> [[u8; 4]; 4] {
+    let mut lines = [[0; 4]; 4];
+
+    for (i, line) in lines.iter_mut().enumerate() {
+        let i = i as u8;
+
+        *line = match direction {
+            Direction::Left  => [i * 4 + 3, i * 4 + 2, i * 4 + 1, i * 4],
+            Direction::Right => [i * 4, i * 4 + 1, i * 4 + 2, i * 4 + 3],
+            Direction::Up    => [12 + i, 8 + i, 4 + i, i],
+            Direction::Down  => [i, 4 + i, 8 + i, 12 + i]
+        };
+    }
+
+    lines
+}
+
+/// Returns a bit mask of the cells that contain a merged tile after moving `board` in `direction`.
+fn merged_cells(board: u64, direction: Direction) -> u16 {
+    let mut merged = 0;
+
+    for line in lines(direction).iter() {
+        let tiles: Vec<u64> = line.iter().map(|&cell| (board >> (cell * 4)) & 0xF).filter(|&tile| tile != 0).collect();
+        let mut i   = 0;
+        let mut pos = 0;
+
+        while i < tiles.len() {
+            if i + 1 < tiles.len() && tiles[i] == tiles[i + 1] {
+                merged |= 1 << line[pos];
+                i      += 2;
+            } else {
+                i += 1;
+            }
+
+            pos += 1;
+        }
+    }
+
+    merged
+}
+
+/// Returns a bit mask of the non-empty cells in `tiles`.
+fn cells(tiles: u64) -> u16 {
+    (0 .. 16).filter(|cell| (tiles >> (cell * 4)) & 0xF != 0).fold(0, |mask, cell| mask | 1 << cell)
+}
+
+/// A single board of the replay along with what happened to get there.
+struct Frame {
+    board:     u64,
+    score:     u64,
+    direction: Option<Direction>,
+    spawned:   u16,
+    merged:    u16
+}
+
+/// Returns a frame for the start board and for the board after every move of `replay`.
+fn frames(replay: &Replay) -> Result<Vec<Frame>, String> {
+    let boards     = replay.boards()?;
+    let mut frames = vec![Frame { board: boards[0], score: 0, direction: None, spawned: 0, merged: 0 }];
+
+    for (i, direction) in replay.moves.iter().enumerate() {
+        let (moved, gained) = Game::execute_scored(boards[i], direction);
+        let score           = frames[i].score + gained;
+
+        frames.push(Frame {
+            board:     boards[i + 1],
+            score,
+            direction: Some(*direction),
+            spawned:   cells(boards[i + 1] ^ moved),
+            merged:    merged_cells(boards[i], *direction)
+        });
+    }
+
+    Ok(frames)
+}
+
+/// Shows `replay` in the terminal until the viewer quits.
+///
+/// Space plays or pauses, left / right (or h / l) step back and forth, g / G jump to the first /
+/// last move, typing a number followed by enter jumps to that move and + / - change the speed.
+pub fn run(replay: &Replay, delay: u64) {
+    let frames = frames(replay).unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        exit(1);
+    });
+
+    let mut term = Terminal::raw().unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        exit(1);
+    });
+
+    let last        = frames.len() - 1;
+    let mut current = 0;
+    let mut delay   = delay;
+    let mut playing = false;
+    let mut jump    = String::new();
+
+    loop {
+        let frame  = &frames[current];
+        let status = if !jump.is_empty() {
+            format!("jump to move: {}", jump)
+        } else if playing {
+            String::from("playing")
+        } else {
+            String::from("paused")
+        };
+
+        term.draw(&format!(
+            "2048 replay\n\nmove: {} / {}   score: {}   last move: {}\n\n{}\n{}   speed: {}ms per move\n\n\
+             space: play / pause, left / right: step, g / G: first / last move,\n\
+             <number> enter: jump to move, + / -: faster / slower, q: quit\n\
+             \x1b[7mspawned\x1b[0m  \x1b[1;4mmerged\x1b[0m\n",
+            current, last, frame.score,
+            frame.direction.map(|d| format!("{:?}", d).to_lowercase()).unwrap_or_else(|| String::from("-")),
+            terminal::highlighted_grid(frame.board, frame.spawned, frame.merged),
+            status, delay
+        )).unwrap();
+
+        let key = if playing {
+            match term.poll_key(Duration::from_millis(delay)) {
+                Ok(Some(key)) => key,
+                Ok(None)      => {
+                    if current < last { current += 1 } else { playing = false }
+                    continue;
+                },
+                Err(_) => break
+            }
+        } else {
+            match term.read_key() {
+                Ok(key) => key,
+                Err(_)  => break
+            }
+        };
+
+        match key {
+            Key::Char(c) if c.is_ascii_digit()               => { jump.push(c); continue },
+            Key::Char('\x7f')                                => { jump.pop(); continue },
+            Key::Char('\r') | Key::Char('\n')                => {
+                if let Ok(target) = jump.parse::<usize>() { current = target.min(last) }
+            },
+            Key::Escape if !jump.is_empty()                  => (),
+            Key::Char(' ')                                   => playing = !playing && current < last,
+            Key::Right | Key::Char('l')                      => current = (current + 1).min(last),
+            Key::Left | Key::Char('h')                       => current = current.saturating_sub(1),
+            Key::Char('g')                                   => current = 0,
+            Key::Char('G')                                   => current = last,
+            Key::Char('+')                                   => delay = (delay / 2).max(25),
+            Key::Char('-')                                   => delay = (delay * 2).min(3200),
+            Key::Char('q') | Key::Escape | Key::Char('\x03') => break,
+            _                                                => ()
+        }
+
+        jump.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merged_cells;
+    use tfe::{Direction, Game};
+
+    const DIRECTIONS: [Direction; 4] = [Direction::Left, Direction::Right, Direction::Up, Direction::Down];
+
+    #[test]
+    fn marks_the_cell_merged_tiles_end_up_in() {
+        // two 2s in cells 0 and 1 of the bottom row
+        assert_eq!(merged_cells(0x11, Direction::Left), 1 << 3);
+        assert_eq!(merged_cells(0x11, Direction::Right), 1 << 0);
+        assert_eq!(merged_cells(0x11, Direction::Up), 0);
+        assert_eq!(merged_cells(0x1111, Direction::Left), 1 << 3 | 1 << 2);
+        assert_eq!(merged_cells(0x0111, Direction::Right), 1 << 0);
+    }
+
+    #[test]
+    fn agrees_with_the_moved_board() {
+        let mut game = Game::new();
+
+        while !Game::is_over(game.board) {
+            for direction in DIRECTIONS.iter() {
+                let (moved, score) = Game::execute_scored(game.board, direction);
+                let merged         = merged_cells(game.board, *direction);
+
+                // every merged tile adds its value to the score
+                let total: u64 = (0 .. 16).filter(|cell| merged >> cell & 1 == 1)
+                                          .map(|cell| 1 << ((moved >> (cell * 4)) & 0xF))
+                                          .sum();
+
+                assert_eq!(total, score);
+            }
+
+            let direction = Game::legal_moves(game.board).iter().next().unwrap();
+            game.step(&direction);
+        }
+    }
+}
@@ -2,7 +2,7 @@ use super::rand::{thread_rng, Rng};
 
 /// Enum that stores all available directions.
 /// This enum also provides some basic functions to allow a game to be using random moves.
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Direction {
     Left,
     Right,
@@ -31,7 +31,24 @@ impl Direction {
     /// // => Direction::Left
     /// ```
     pub fn sample() -> Direction {
-        match thread_rng().gen_range(0, 4) {
+        Self::sample_rng(&mut thread_rng())
+    }
+
+    /// Like `tfe::Direction::sample` but draws from `rng` instead of `thread_rng()`.
+    /// The same seed always yields the same sequence of directions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Direction, GameRng};
+    ///
+    /// let a: Vec<Direction> = (0 .. 8).scan(GameRng::new(3), |rng, _| Some(Direction::sample_rng(rng))).collect();
+    /// let b: Vec<Direction> = (0 .. 8).scan(GameRng::new(3), |rng, _| Some(Direction::sample_rng(rng))).collect();
+    ///
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn sample_rng<R: Rng + ?Sized>(rng: &mut R) -> Direction {
+        match rng.gen_range(0, 4) {
             0 => Direction::Left,
             1 => Direction::Right,
             2 => Direction::Down,
@@ -40,7 +57,7 @@ impl Direction {
         }
     }
 
-    /// Returns a `Vec<Direction>` excluding `dirs: &Vec<Direction>`.
+    /// Returns a `Vec<Direction>` excluding `dirs: &[Direction]`.
     ///
     /// # Examples
     ///
@@ -51,14 +68,14 @@ impl Direction {
     ///
     /// assert_eq!(available, vec![Direction::Up, Direction::Down]);
     /// ```
-    pub fn without(dirs: &Vec<Direction>) -> Vec<Direction> {
+    pub fn without(dirs: &[Direction]) -> Vec<Direction> {
         let mut filtered = DIRECTIONS.clone();
-        filtered.retain(|dir| dirs.iter().all(|tried| &dir != &tried));
+        filtered.retain(|dir| dirs.iter().all(|tried| dir != tried));
         filtered
     }
 
     /// Like `tfe::Direction::sample` but combined with `tfe::Direction::without`.
-    /// Returns a `Direction` after excluding `dirs: &Vec<Direction>`.
+    /// Returns a `Direction` after excluding `dirs: &[Direction]`.
     ///
     /// # Examples
     ///
@@ -71,9 +88,153 @@ impl Direction {
     /// let up = Direction::sample_without(&vec![Direction::Down, Direction::Left, Direction::Right]);
     /// assert_eq!(Direction::Up, up);
     /// ```
-    pub fn sample_without(dirs: &Vec<Direction>) -> Direction {
+    pub fn sample_without(dirs: &[Direction]) -> Direction {
+        Self::sample_without_rng(dirs, &mut thread_rng())
+    }
+
+    /// Like `tfe::Direction::sample_without` but draws from `rng` instead of `thread_rng()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Direction, GameRng};
+    ///
+    /// let mut rng   = GameRng::new(0);
+    /// let direction = Direction::sample_without_rng(&[Direction::Up, Direction::Down], &mut rng);
+    ///
+    /// assert!(vec![Direction::Left, Direction::Right].contains(&direction));
+    /// ```
+    pub fn sample_without_rng<R: Rng + ?Sized>(dirs: &[Direction], rng: &mut R) -> Direction {
         let filtered = &Self::without(dirs);
 
-        filtered[thread_rng().gen_range(0, filtered.len())].clone()
+        filtered[rng.gen_range(0, filtered.len())]
+    }
+
+    /// Returns the bit used to store this `Direction` in a `tfe::DirectionSet`.
+    pub fn bit(self) -> u8 {
+        match self {
+            Direction::Left  => 0b0001,
+            Direction::Right => 0b0010,
+            Direction::Up    => 0b0100,
+            Direction::Down  => 0b1000
+        }
+    }
+
+    /// Returns the 2 bit code used to store this `Direction` in replay and save files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::Direction;
+    ///
+    /// assert_eq!(Direction::Up.code(), 2);
+    /// assert_eq!(Direction::from_code(Direction::Up.code()), Some(Direction::Up));
+    /// assert_eq!(Direction::from_code(4), None);
+    /// ```
+    pub fn code(self) -> u8 {
+        match self {
+            Direction::Left  => 0,
+            Direction::Right => 1,
+            Direction::Up    => 2,
+            Direction::Down  => 3
+        }
+    }
+
+    /// Returns the `Direction` stored as `code`, see `tfe::Direction::code`.
+    /// Returns `None` when `code` is larger than `3`.
+    pub fn from_code(code: u8) -> Option<Direction> {
+        DIRECTIONS.get(code as usize).cloned()
+    }
+}
+
+/// A set of `Direction`s stored as 4 bits, one bit per direction.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::{Direction, DirectionSet};
+///
+/// let mut set = DirectionSet::empty();
+/// set.insert(Direction::Left);
+/// set.insert(Direction::Up);
+///
+/// assert!(set.contains(Direction::Left));
+/// assert!(!set.contains(Direction::Down));
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(set.to_vec(), vec![Direction::Left, Direction::Up]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DirectionSet { bits: u8 }
+
+impl DirectionSet {
+    /// Returns a set without any directions.
+    pub fn empty() -> Self {
+        DirectionSet { bits: 0 }
+    }
+
+    /// Returns a set containing every direction.
+    pub fn all() -> Self {
+        DirectionSet { bits: 0b1111 }
+    }
+
+    /// Returns a set from its 4-bit representation, see `tfe::Direction::bit`.
+    /// Bits other than the lowest 4 are ignored.
+    pub fn from_bits(bits: u8) -> Self {
+        DirectionSet { bits: bits & 0b1111 }
+    }
+
+    /// Returns the 4-bit representation of this set.
+    pub fn bits(self) -> u8 {
+        self.bits
+    }
+
+    /// Returns `true` if `direction` is part of this set.
+    pub fn contains(self, direction: Direction) -> bool {
+        self.bits & direction.bit() != 0
+    }
+
+    /// Adds `direction` to this set.
+    pub fn insert(&mut self, direction: Direction) {
+        self.bits |= direction.bit();
+    }
+
+    /// Removes `direction` from this set.
+    pub fn remove(&mut self, direction: Direction) {
+        self.bits &= !direction.bit();
+    }
+
+    /// Returns the number of directions in this set.
+    pub fn len(self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Returns `true` if this set contains no directions.
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns a set containing every direction that is not part of this set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Direction, DirectionSet};
+    ///
+    /// let set = DirectionSet::from_bits(Direction::Left.bit() | Direction::Right.bit());
+    ///
+    /// assert_eq!(set.complement().to_vec(), vec![Direction::Up, Direction::Down]);
+    /// ```
+    pub fn complement(self) -> Self {
+        DirectionSet::from_bits(!self.bits)
+    }
+
+    /// Returns an iterator over the directions in this set in the order left, right, up, down.
+    pub fn iter(self) -> impl Iterator<Item = Direction> {
+        DIRECTIONS.iter().cloned().filter(move |dir| self.contains(*dir))
+    }
+
+    /// Returns the directions in this set as a `Vec<Direction>`.
+    pub fn to_vec(self) -> Vec<Direction> {
+        self.iter().collect()
     }
 }
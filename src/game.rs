@@ -1,6 +1,10 @@
 use std::ops::Add;
 use super::rand::{thread_rng, Rng};
-use super::direction::Direction;
+use super::rand::rngs::ThreadRng;
+use super::direction::{Direction, DirectionSet};
+use super::rng::GameRng;
+use super::spawn::{SpawnRules, Spawn};
+use super::agent::{Agent, FnAgent, GameState, GameEnd, ILLEGAL_MOVE_LIMIT};
 
 /// A mask with a single section of 16 bits set to 0.
 /// Used to extract a "horizontal slice" out of a 64 bit integer.
@@ -11,17 +15,25 @@ pub static ROW_MASK: u64 = 0xFFFF;
 pub static COL_MASK: u64 = 0x000F_000F_000F_000F_u64;
 
 /// Struct that contains all available moves per row for up, down, right and left.
-/// Also stores the score for a given row.
+/// Also stores the score for a given row and the score gained by merging a row left or right.
+///
+/// `can_move` stores for every row whether it changes when moved left (`Direction::Left.bit()`)
+/// and / or right (`Direction::Right.bit()`).
 ///
 /// Moves are stored as power values for tiles.
-/// if a power value is `> 0`, print the tile value using `2 << tile` where tile is any 4-bit
+/// if a power value is `> 0`, print the tile value using `1 << tile` where tile is any 4-bit
 /// "nybble" otherwise print a `0` instead.
 struct Moves {
     pub left:   Vec<u64>,
     pub right:  Vec<u64>,
     pub down:   Vec<u64>,
     pub up:     Vec<u64>,
-    pub scores: Vec<u64>
+    pub scores: Vec<u64>,
+
+    pub merge_left:  Vec<u64>,
+    pub merge_right: Vec<u64>,
+
+    pub can_move: Vec<u8>
 }
 
 impl Moves {
@@ -47,6 +59,8 @@ lazy_static! {
         let mut up_moves    = vec![0; 65536];
         let mut down_moves  = vec![0; 65536];
         let mut scores      = vec![0; 65536];
+        let mut merge_left  = vec![0; 65536];
+        let mut merge_right = vec![0; 65536];
 
         for row in 0 .. 65536 {
             // break row into cells
@@ -60,13 +74,14 @@ lazy_static! {
             // calculate score for given row
             let mut s = 0;
 
-            for i in 0 .. 4 {
-                if line[i] > 1 { s += (line[i] - 1) * (2 << line[i]) }
+            for tile in &line {
+                if *tile > 1 { s += (tile - 1) * (1 << tile) }
             }
 
             scores[row as usize] = s;
 
-            let mut i = 0;
+            let mut i      = 0;
+            let mut merged = 0;
 
             // perform a move to the left using current {row} as board
             // generates 4 output moves for up, down, left and right by transposing and reversing
@@ -96,6 +111,7 @@ lazy_static! {
                 } else if line[i] == line[j] {
                     if line[i] != 0xF { line[i] += 1 };
                     line[j] = 0;
+                    merged += 1 << line[i];
                 }
 
                 // finally, move to the next (or current, if i was 0) row
@@ -121,12 +137,43 @@ lazy_static! {
             left_moves[rev_idx]  = rev_row                     ^ rev_res;
             up_moves[rev_idx]    = Moves::column_from(rev_row) ^ Moves::column_from(rev_res);
             down_moves[row_idx]  = Moves::column_from(row)     ^ Moves::column_from(result);
+
+            // merging a row left scores the same as merging its reverse right.
+            merge_right[row_idx] = merged;
+            merge_left[rev_idx]  = merged;
         };
 
-        Moves { left: left_moves, right: right_moves, down: down_moves, up: up_moves, scores: scores }
+        // a row can move in a direction when the move changes at least one of its tiles.
+        let can_move = (0 .. 65536).map(|row| {
+            (if left_moves[row]  != 0 { Direction::Left.bit() }  else { 0 }) |
+            (if right_moves[row] != 0 { Direction::Right.bit() } else { 0 })
+        }).collect();
+
+        Moves { left: left_moves, right: right_moves, down: down_moves, up: up_moves, scores, merge_left, merge_right, can_move }
     };
 }
 
+/// Struct returned by `tfe::Game::step` describing what a single move did.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StepResult {
+    /// `true` if the move changed the board, a move that did nothing does not spawn a tile.
+    pub moved: bool,
+    /// The tiles that spawned after the move as a board containing only those tiles.
+    /// This is `0` when the move did not change the board.
+    pub spawned: u64,
+    /// The score gained by merging tiles during this move.
+    pub score: u64,
+    /// `true` if no move can change the board anymore.
+    pub over: bool
+}
+
+impl StepResult {
+    /// Returns each spawned tile as a `tfe::Spawn`.
+    pub fn spawns(&self) -> Vec<Spawn> {
+        Spawn::from_tiles(self.spawned)
+    }
+}
+
 /// Struct used to play a single game of 2048.
 ///
 /// `tfe::Game` uses a single `u64` as board value.
@@ -136,7 +183,306 @@ lazy_static! {
 /// All manipulations are done using bit-shifts and a precomputed table of moves and scores.
 /// Every move is stored as four lookups total, one for each row. The result of XOR'ing each row
 /// back into the board at the right position is the output board.
-pub struct Game { pub board: u64 }
+///
+/// Every random decision a game makes is drawn from its own random number generator `R`.
+/// By default this is `thread_rng()`, use `Game::with_rng` or `Game::play_seeded` to make a game
+/// reproducible.
+///
+/// Which tiles spawn and how many of them is decided by the game's `tfe::SpawnRules`.
+///
+/// Besides the board, a game keeps track of its exact `score`, the number of `moves` that changed
+/// the board and the number of `fours` that spawned, including the starting tiles.
+pub struct Game<R = ThreadRng> {
+    pub board: u64,
+    pub score: u64,
+    pub moves: u64,
+    pub fours: u64,
+
+    rng:                   R,
+    rules:                 SpawnRules,
+    pub(crate) last_spawn: Option<Spawn>
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Rng> Game<R> {
+    /// Like `tfe::Game::new` but uses `rng` for every random decision this game makes.
+    /// Two games constructed with equally seeded generators start with the same board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng};
+    ///
+    /// let a = Game::with_rng(GameRng::new(42));
+    /// let b = Game::with_rng(GameRng::new(42));
+    ///
+    /// assert_eq!(a.board, b.board);
+    /// ```
+    pub fn with_rng(rng: R) -> Self {
+        Self::with_rng_and_rules(rng, SpawnRules::default())
+    }
+
+    /// Like `tfe::Game::with_rng` but spawns tiles according to `rules`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng, SpawnRules};
+    ///
+    /// let rules = SpawnRules { start_tiles: 4, ..SpawnRules::default() };
+    /// let game  = Game::with_rng_and_rules(GameRng::new(42), rules);
+    ///
+    /// assert_eq!(Game::count_empty(game.board), 12);
+    /// ```
+    pub fn with_rng_and_rules(rng: R, rules: SpawnRules) -> Self {
+        let mut game = Self::from_board_with(0x0000_0000_0000_0000_u64, rng, rules);
+
+        for _ in 0 .. game.rules.start_tiles {
+            game.spawn_one();
+        }
+
+        game
+    }
+
+    /// Like `tfe::Game::play` but uses `rng` for every random decision this game makes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng, Direction};
+    ///
+    /// let game = Game::play_with_rng(GameRng::new(1), |_board, failed| Direction::sample_without(failed));
+    /// # println!("{:016x}", game.board);
+    /// ```
+    pub fn play_with_rng<F: FnMut(u64, &Vec<Direction>) -> Direction>(rng: R, mv: F) -> Self {
+        Self::play_with_rng_and_rules(rng, SpawnRules::default(), mv)
+    }
+
+    /// Like `tfe::Game::play_with_rng` but spawns tiles according to `rules`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng, SpawnRules, Direction};
+    ///
+    /// let rules = SpawnRules { tiles_per_move: 2, ..SpawnRules::default() };
+    /// let game  = Game::play_with_rng_and_rules(GameRng::new(1), rules, |_board, failed| Direction::sample_without(failed));
+    /// # println!("{:016x}", game.board);
+    /// ```
+    pub fn play_with_rng_and_rules<F: FnMut(u64, &Vec<Direction>) -> Direction>(rng: R, rules: SpawnRules, mv: F) -> Self {
+        let mut game = Self::with_rng_and_rules(rng, rules);
+
+        game.run(&mut FnAgent(mv));
+        game
+    }
+
+    /// Constructs a `tfe::Game` from an existing `board` instead of spawning starting tiles.
+    /// All tiles spawned afterwards are drawn from `rng` according to `rules`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `rules` are invalid, see `tfe::SpawnRules::validate`. Every other constructor
+    /// that takes `SpawnRules` panics in the same way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng, SpawnRules};
+    ///
+    /// let game = Game::from_board_with(0x0000_0000_0000_1100, GameRng::new(0), SpawnRules::default());
+    ///
+    /// assert_eq!(game.board, 0x0000_0000_0000_1100);
+    /// ```
+    pub fn from_board_with(board: u64, rng: R, rules: SpawnRules) -> Self {
+        if let Err(err) = rules.validate() { panic!("invalid spawn rules: {}", err) }
+
+        Game { board, score: 0, moves: 0, fours: 0, rng, rules, last_spawn: None }
+    }
+
+    /// Moves the board in `direction` and spawns new tiles if the board changed.
+    ///
+    /// Unlike `tfe::Game::play`, which owns the whole game loop, `step` allows a game to be
+    /// driven one move at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng, SpawnRules, Direction};
+    ///
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 2 | 2 | 0 | 0 |
+    /// let mut game = Game::from_board_with(0x0000_0000_0000_1100, GameRng::new(0), SpawnRules::default());
+    ///
+    /// let result = game.step(&Direction::Up);
+    /// assert!(result.moved);
+    /// assert_eq!(result.score, 0);
+    ///
+    /// // both 2s are now in the top row, merging them scores 4 points
+    /// let result = game.step(&Direction::Left);
+    /// assert!(result.moved);
+    /// assert_eq!(result.score, 4);
+    /// assert_eq!(result.spawns().len(), 1);
+    /// assert!(!result.over);
+    ///
+    /// let stuck = Game::from_board(0x1212_2121_1212_2121).step(&Direction::Left);
+    /// assert!(!stuck.moved);
+    /// assert!(stuck.over);
+    /// ```
+    pub fn step(&mut self, direction: &Direction) -> StepResult {
+        let moved_board = Game::execute(self.board, direction);
+
+        if moved_board == self.board {
+            return StepResult { moved: false, spawned: 0, score: 0, over: Game::is_over(self.board) };
+        }
+
+        let score   = Game::merge_score(self.board, direction);
+        self.board  = moved_board;
+        self.score += score;
+        self.moves += 1;
+        self.spawn();
+
+        StepResult {
+            moved:   true,
+            spawned: self.board ^ moved_board,
+            score,
+            over:    Game::is_over(self.board)
+        }
+    }
+
+    /// Returns the `tfe::SpawnRules` used by this game.
+    pub fn rules(&self) -> &SpawnRules {
+        &self.rules
+    }
+
+    /// Plays this game to completion using `agent` to choose every move.
+    ///
+    /// Calls `agent.reset()` before the first move and `agent.on_game_end` once the game ended.
+    /// An agent that keeps choosing moves that do not change the board is stopped after
+    /// `tfe::ILLEGAL_MOVE_LIMIT` attempts in a row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Agent, Direction, Game, GameEnd, GameState};
+    ///
+    /// // an agent that only ever moves left
+    /// struct Stubborn;
+    ///
+    /// impl Agent for Stubborn {
+    ///     fn choose(&mut self, _state: &GameState) -> Direction { Direction::Left }
+    /// }
+    ///
+    /// let mut game = Game::new();
+    ///
+    /// assert_eq!(game.run(&mut Stubborn), GameEnd::IllegalMoves);
+    /// ```
+    pub fn run<A: Agent + ?Sized>(&mut self, agent: &mut A) -> GameEnd {
+        let mut illegal = 0;
+
+        agent.reset();
+
+        while !Game::is_over(self.board) {
+            let state     = self.state();
+            let direction = agent.choose(&state);
+
+            if state.legal.contains(direction) {
+                self.step(&direction);
+                illegal = 0;
+            } else {
+                illegal += 1;
+                if illegal == ILLEGAL_MOVE_LIMIT { break }
+            }
+        }
+
+        agent.on_game_end(&self.state());
+
+        if Game::is_over(self.board) { GameEnd::Over } else { GameEnd::IllegalMoves }
+    }
+
+    /// Returns the current `tfe::GameState` of this game as seen by an `tfe::Agent`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, Direction};
+    ///
+    /// let state = Game::from_board(0x0000_0000_0000_1234).state();
+    ///
+    /// assert_eq!(state.moves, 0);
+    /// assert_eq!(state.last_spawn, None);
+    /// assert_eq!(state.legal.to_vec(), vec![Direction::Up]);
+    /// ```
+    pub fn state(&self) -> GameState {
+        GameState {
+            board:      self.board,
+            score:      self.score,
+            moves:      self.moves,
+            last_spawn: self.last_spawn,
+            legal:      Game::legal_moves(self.board)
+        }
+    }
+
+    /// Spawns `tiles_per_move` tiles onto the board according to the rules of this game.
+    fn spawn(&mut self) {
+        for _ in 0 .. self.rules.tiles_per_move {
+            match self.spawn_one() {
+                Some(spawn) => self.last_spawn = Some(spawn),
+                None        => break
+            }
+        }
+    }
+
+    /// Spawns a single tile onto the board and counts it in `fours` if it is a `4`.
+    /// Returns `None` when the board has no empty tiles left.
+    fn spawn_one(&mut self) -> Option<Spawn> {
+        let tile = Game::spawn_tile_with(self.board, &self.rules, &mut self.rng);
+
+        if tile == 0 { return None }
+
+        let index = (tile.trailing_zeros() / 4) as usize;
+        let value = tile >> (index * 4);
+
+        if value == 2 { self.fours += 1 }
+
+        self.board |= tile;
+
+        Some(Spawn { index, value })
+    }
+
+    /// Returns a mutable reference to the random number generator used by this game.
+    /// Agents can use it to make their own random decisions reproducible as well.
+    pub fn rng(&mut self) -> &mut R {
+        &mut self.rng
+    }
+}
+
+impl Game<GameRng> {
+    /// Like `tfe::Game::play` but seeds the game with `seed`.
+    /// Playing the same seed with the same sequence of moves always produces the exact same
+    /// spawns and thus the same final board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, Direction};
+    ///
+    /// let a = Game::play_seeded(42, |_board, failed| Direction::without(failed)[0]);
+    /// let b = Game::play_seeded(42, |_board, failed| Direction::without(failed)[0]);
+    ///
+    /// assert_eq!(a.board, b.board);
+    /// ```
+    pub fn play_seeded<F: FnMut(u64, &Vec<Direction>) -> Direction>(seed: u64, mv: F) -> Self {
+        Self::play_with_rng(GameRng::new(seed), mv)
+    }
+}
+
 impl Game {
     /// Constructs a new `tfe::Game`.
     ///
@@ -162,20 +508,17 @@ impl Game {
     /// println!("{:016x}", game.board);
     /// ```
     pub fn new() -> Self {
-        let mut game = Game { board: 0x0000_0000_0000_0000_u64 };
-
-        game.board |= Self::spawn_tile(game.board);
-        game.board |= Self::spawn_tile(game.board);
-
-        game
+        Self::with_rng(thread_rng())
     }
 
     /// Like `new` but takes a closure that accepts two parameters and returns
     /// a `Direction`. The parameters passed to the closure:
     ///
     /// - `u64`: The current board
-    /// - `&Vec<Direction>`: A list of attempted moves that had no effect.
-    /// Gets cleared when a move succeeds.
+    /// - `&Vec<Direction>`: A list of moves that have no effect on the current board.
+    ///
+    /// The closure is run as an `tfe::Agent`, see `tfe::Game::run` for more control over how a
+    /// game is played.
     ///
     /// # Examples
     ///
@@ -191,28 +534,67 @@ impl Game {
     /// completion. A game is over when it has no moves left. This is true when all possible
     /// moves return the same resulting board as before the move was executed.
     ///
-    /// The `failed: &Vec<Direction>` will contain **at most** 3 items, when the 4th item is added
-    /// the game ends automatically without calling the closure again.
-    pub fn play<F: Fn(u64, &Vec<Direction>) -> Direction>(mv: F) -> Self {
-        let mut game = Self::new();
-        let mut attempted: Vec<Direction> = Vec::with_capacity(4);
+    /// The game ends as soon as no move can change the board anymore, see `tfe::Game::is_over`.
+    /// This means that `failed: &Vec<Direction>` will contain **at most** 3 items.
+    ///
+    /// A closure that returns one of the `failed` moves is called again, up to 64 times per move
+    /// before the move counts towards `tfe::ILLEGAL_MOVE_LIMIT`. A closure that picks random moves
+    /// without looking at `failed` still plays to completion, one that never returns a move that
+    /// changes the board ends the game early.
+    ///
+    /// ```
+    /// use tfe::{Game, Direction};
+    ///
+    /// // `Direction::sample` ignores `failed` but the game is still played to completion
+    /// let game = Game::play(|_board, _failed| Direction::sample());
+    ///
+    /// assert!(Game::is_over(game.board));
+    ///
+    /// // a closure that only ever moves down stops once the board can no longer move down
+    /// let mut calls = 0;
+    /// let stubborn  = Game::play(|_board, _failed| { calls += 1; Direction::Down });
+    ///
+    /// assert!(Game::execute(stubborn.board, &Direction::Down) == stubborn.board);
+    /// assert!(calls < 1000);
+    /// ```
+    pub fn play<F: FnMut(u64, &Vec<Direction>) -> Direction>(mv: F) -> Self {
+        Self::play_with_rng(thread_rng(), mv)
+    }
 
-        loop {
-            let mv = mv(game.board, &attempted);
-            if !attempted.iter().any(|dir| dir == &mv) {
-                let result_board = Self::execute(game.board, &mv);
-
-                if game.board == result_board {
-                    if attempted.len() == 3 { break }
-                    attempted.push(mv);
-                } else {
-                    game.board = result_board | Self::spawn_tile(result_board);
-                    attempted.clear();
-                }
-            }
-        }
+    /// Constructs a `tfe::Game` from an existing `board` using `thread_rng()` and the standard
+    /// `tfe::SpawnRules`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::Game;
+    ///
+    /// let game = Game::from_board(0x0000_0000_0000_2211);
+    ///
+    /// assert_eq!(game.board, 0x0000_0000_0000_2211);
+    /// ```
+    pub fn from_board(board: u64) -> Self {
+        Self::from_board_with(board, thread_rng(), SpawnRules::default())
+    }
 
-        game
+    /// Like `tfe::Game::new` but spawns tiles according to `rules`.
+    pub fn with_rules(rules: SpawnRules) -> Self {
+        Self::with_rng_and_rules(thread_rng(), rules)
+    }
+
+    /// Like `tfe::Game::play` but spawns tiles according to `rules`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, SpawnRules, Direction};
+    ///
+    /// let rules = SpawnRules { four_probability: 0.5, ..SpawnRules::default() };
+    /// let game  = Game::play_with_rules(rules, |_board, failed| Direction::sample_without(failed));
+    /// # println!("{:016x}", game.board);
+    /// ```
+    pub fn play_with_rules<F: FnMut(u64, &Vec<Direction>) -> Direction>(rules: SpawnRules, mv: F) -> Self {
+        Self::play_with_rng_and_rules(thread_rng(), rules, mv)
     }
 
     /// Returns `board` moved in given `direction`.
@@ -249,6 +631,28 @@ impl Game {
         }
     }
 
+    /// Like `tfe::Game::execute` but also returns the score gained by the move.
+    /// The score of a move is the sum of the values of all tiles created by merging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, Direction};
+    ///
+    /// // | 0 | 0 | 0 | 0 |      | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |  =>  | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 4 | 4 |      | 8 | 0 | 0 | 0 |
+    /// // | 2 | 2 | 2 | 0 |      | 4 | 2 | 0 | 0 |
+    ///
+    /// let (moved, score) = Game::execute_scored(0x0000_0000_0022_1110, &Direction::Left);
+    ///
+    /// assert_eq!(moved, 0x0000_0000_3000_2100);
+    /// assert_eq!(score, 8 + 4);
+    /// ```
+    pub fn execute_scored(board: u64, direction: &Direction) -> (u64, u64) {
+        (Self::execute(board, direction), Self::merge_score(board, direction))
+    }
+
     /// Returns a transposed board where rows are transformed into columns and vice versa.
     ///
     /// ```
@@ -393,6 +797,78 @@ impl Game {
         result
     }
 
+    /// Returns the sum of all tiles created by merging when moving `board` in `direction`.
+    fn merge_score(board: u64, direction: &Direction) -> u64 {
+        match direction {
+            Direction::Left  => Self::table_helper(board, &MOVES.merge_left),
+            Direction::Right => Self::table_helper(board, &MOVES.merge_right),
+            Direction::Up    => Self::table_helper(Self::transpose(board), &MOVES.merge_left),
+            Direction::Down  => Self::table_helper(Self::transpose(board), &MOVES.merge_right)
+        }
+    }
+
+    /// Returns the set of directions that change `board` when moved in.
+    /// Like a move, this costs four lookups for the rows and four for the transposed columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, Direction};
+    ///
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 2 | 4 | 8 | 16 |
+    ///
+    /// let legal = Game::legal_moves(0x0000_0000_0000_1234);
+    ///
+    /// assert_eq!(legal.to_vec(), vec![Direction::Up]);
+    /// ```
+    pub fn legal_moves(board: u64) -> DirectionSet {
+        let transposed = Self::transpose(board);
+        let rows       = MOVES.can_move[((board      >>  0) & ROW_MASK) as usize] |
+                         MOVES.can_move[((board      >> 16) & ROW_MASK) as usize] |
+                         MOVES.can_move[((board      >> 32) & ROW_MASK) as usize] |
+                         MOVES.can_move[((board      >> 48) & ROW_MASK) as usize];
+        let cols       = MOVES.can_move[((transposed >>  0) & ROW_MASK) as usize] |
+                         MOVES.can_move[((transposed >> 16) & ROW_MASK) as usize] |
+                         MOVES.can_move[((transposed >> 32) & ROW_MASK) as usize] |
+                         MOVES.can_move[((transposed >> 48) & ROW_MASK) as usize];
+
+        // moving a transposed row left / right moves the column up / down.
+        DirectionSet::from_bits(rows | (cols << 2))
+    }
+
+    /// Returns `true` if no move changes `board` anymore.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::Game;
+    ///
+    /// assert!(Game::is_over(0x1212_2121_1212_2121));
+    /// assert!(!Game::is_over(0x1212_2121_1212_2122));
+    /// ```
+    pub fn is_over(board: u64) -> bool {
+        Self::legal_moves(board).is_empty()
+    }
+
+    /// Returns the value of the highest tile on `board`, `0` for an empty board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::Game;
+    ///
+    /// assert_eq!(Game::highest_tile(0x0000_0000_00B0_2211_u64), 2048);
+    /// assert_eq!(Game::highest_tile(0), 0);
+    /// ```
+    pub fn highest_tile(board: u64) -> u64 {
+        let highest = (0 .. 16).map(|i| (board >> (i * 4)) & 0xF).max().unwrap_or(0);
+
+        if highest == 0 { 0 } else { 1 << highest }
+    }
+
     /// Returns the count of tiles with a value of `0`.
     ///
     /// # Examples
@@ -414,7 +890,7 @@ impl Game {
     }
 
     /// Returns the sum of 4 lookups in `table` for each "row" in `board`.
-    pub fn table_helper<T: Clone + Add<Output = T>>(board: u64, table: &Vec<T>) -> T {
+    pub fn table_helper<T: Clone + Add<Output = T>>(board: u64, table: &[T]) -> T {
         table[((board >>  0) & ROW_MASK) as usize].clone() +
         table[((board >> 16) & ROW_MASK) as usize].clone() +
         table[((board >> 32) & ROW_MASK) as usize].clone() +
@@ -423,20 +899,109 @@ impl Game {
 
     /// Returns the score of a given `board`.
     /// The score of a single tile is the sum of the tile value and all intermediate merged tiles.
+    ///
+    /// This is an estimate which assumes every tile was built from `2`s, it over-counts by `4`
+    /// points for every `4` that spawned. Use `tfe::Game::estimate_score` when the number of
+    /// spawned `4`s is known or the `score` of a `tfe::Game` for the exact score.
+    ///
+    /// Tile values are `1 << power`, earlier releases used `2 << power` which doubled every score.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::Game;
+    ///
+    /// // a `4` merged from two `2`s scores 4 points, an `8` scores 4 + 4 + 8 points
+    /// assert_eq!(Game::score(0x0000_0000_0000_0002), 4);
+    /// assert_eq!(Game::score(0x0000_0000_0000_0003), 16);
+    /// ```
     pub fn score(board: u64) -> u64 {
         Self::table_helper(board, &MOVES.scores)
     }
 
+    /// Returns the score of a given `board` corrected for `fours` spawned `4`s.
+    /// This equals the exact score of a game with standard `tfe::SpawnRules`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng, Direction};
+    ///
+    /// for seed in 0 .. 50 {
+    ///     let game = Game::play_with_rng(GameRng::new(seed), |_board, failed| Direction::without(failed)[0]);
+    ///
+    ///     assert_eq!(Game::estimate_score(game.board, game.fours), game.score);
+    /// }
+    /// ```
+    pub fn estimate_score(board: u64, fours: u64) -> u64 {
+        Self::score(board).saturating_sub(4 * fours)
+    }
+
     /// Returns a `2` with 90% chance and `4` with 10% chance.
     pub fn tile() -> u64 {
-        if thread_rng().gen_range(0, 10) == 10 { 2 } else { 1 }
+        Self::tile_rng(&mut thread_rng())
+    }
+
+    /// Like `tfe::Game::tile` but draws from `rng` instead of `thread_rng()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng};
+    ///
+    /// let mut rng = GameRng::new(0);
+    /// let fours   = (0 .. 10_000).filter(|_| Game::tile_rng(&mut rng) == 2).count();
+    ///
+    /// assert!(fours > 800 && fours < 1200);
+    /// ```
+    pub fn tile_rng<R: Rng + ?Sized>(rng: &mut R) -> u64 {
+        SpawnRules::default().tile(rng)
     }
 
-    /// Returns a `1` shifted to the position of any `0` bit in `board` randomly.
+    /// Returns a new tile shifted to the position of any empty tile in `board` randomly.
     pub fn spawn_tile(board: u64) -> u64 {
+        Self::spawn_tile_rng(board, &mut thread_rng())
+    }
+
+    /// Like `tfe::Game::spawn_tile` but draws from `rng` instead of `thread_rng()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng};
+    ///
+    /// let board = 0x1111_1111_1111_0111_u64;
+    /// let tile  = Game::spawn_tile_rng(board, &mut GameRng::new(5));
+    ///
+    /// // only a single empty cell remains, the tile always spawns there
+    /// assert!(tile == 0x0000_0000_0000_1000 || tile == 0x0000_0000_0000_2000);
+    /// ```
+    pub fn spawn_tile_rng<R: Rng + ?Sized>(board: u64, rng: &mut R) -> u64 {
+        Self::spawn_tile_with(board, &SpawnRules::default(), rng)
+    }
+
+    /// Like `tfe::Game::spawn_tile_rng` but picks the value of the tile according to `rules`,
+    /// which must be valid, see `tfe::SpawnRules::validate`.
+    /// Returns `0` when `board` has no empty tiles left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng, SpawnRules};
+    ///
+    /// let rules = SpawnRules { four_probability: 1.0, ..SpawnRules::default() };
+    ///
+    /// assert_eq!(Game::spawn_tile_with(0x1111_1111_1111_0111, &rules, &mut GameRng::new(0)), 0x2000);
+    /// assert_eq!(Game::spawn_tile_with(0x1111_1111_1111_1111, &rules, &mut GameRng::new(0)), 0);
+    /// ```
+    pub fn spawn_tile_with<R: Rng + ?Sized>(board: u64, rules: &SpawnRules, rng: &mut R) -> u64 {
+        let empty = Self::count_empty(board);
+
+        if empty == 0 { return 0 }
+
         let mut tmp = board;
-        let mut idx = thread_rng().gen_range(0, Self::count_empty(board));
-        let mut t   = Self::tile();
+        let mut idx = rng.gen_range(0, empty);
+        let mut t   = rules.tile(rng);
 
         loop {
             while (tmp & 0xF) != 0 {
@@ -452,4 +1017,33 @@ impl Game {
 
         t
     }
+
+    /// Like `tfe::Game::spawn_tile_with` but spawns `rules.tiles_per_move` tiles, just like a
+    /// `tfe::Game` does after every move. Returns every spawned tile as a single board, fewer
+    /// tiles spawn when `board` fills up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Game, GameRng, SpawnRules};
+    ///
+    /// let rules   = SpawnRules { tiles_per_move: 2, ..SpawnRules::default() };
+    /// let spawned = Game::spawn_tiles_with(0x0000_0000_0000_0000, &rules, &mut GameRng::new(0));
+    ///
+    /// assert_eq!(Game::count_empty(spawned), 14);
+    /// assert_eq!(Game::spawn_tiles_with(0x1111_1111_1111_0111, &rules, &mut GameRng::new(0)).count_ones(), 1);
+    /// ```
+    pub fn spawn_tiles_with<R: Rng + ?Sized>(board: u64, rules: &SpawnRules, rng: &mut R) -> u64 {
+        let mut spawned = 0;
+
+        for _ in 0 .. rules.tiles_per_move {
+            let tile = Self::spawn_tile_with(board | spawned, rules, rng);
+
+            if tile == 0 { break }
+
+            spawned |= tile;
+        }
+
+        spawned
+    }
 }
@@ -0,0 +1,371 @@
+//! Heuristic used to score boards for the agents in `tfe::ai`.
+//!
+//! The heuristic is made up of several terms which are computed for every possible row and
+//! stored in a lookup table. A board is scored by looking up each of its rows and each of its
+//! columns, which means that evaluating a board costs eight lookups.
+//!
+//! - `empty`: the number of empty tiles.
+//! - `merges`: the number of tiles that can be merged with a neighbouring tile.
+//! - `monotonicity`: how far a row is from being monotonic, this is a penalty.
+//! - `sum`: the sum of all tile powers raised to a power, this is a penalty.
+//! - `smoothness`: the difference between neighbouring non-empty tiles, this is a penalty.
+//!
+//! How much each term contributes to the score is configured using `HeuristicParams`, which can
+//! be read from a TOML or JSON file:
+//!
+//! ```
+//! use tfe::heuristic::{Heuristic, HeuristicParams};
+//!
+//! let params    = HeuristicParams::parse("merge_weight = 700.0\nsum_power = 3.0").unwrap();
+//! let heuristic = Heuristic::with_params(&params);
+//!
+//! assert_eq!(heuristic.params.merge_weight, 700.0);
+//! assert_eq!(heuristic.params.empty_weight, 270.0);
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::ops::Add;
+use std::path::Path;
+use std::sync::Arc;
+use super::game::Game;
+
+/// Struct that contains the weight of each heuristic term and the powers used to compute them.
+///
+/// `HeuristicParams::default()` returns the weights the built-in agents use.
+#[derive(Clone, PartialEq, Debug)]
+pub struct HeuristicParams {
+    /// Reward per empty tile.
+    pub empty_weight:  f64,
+    /// Reward per pair of tiles that can be merged.
+    pub merge_weight:  f64,
+    /// Penalty per unit of `tfe::heuristic::Terms::monotonicity`.
+    pub mono_weight:   f64,
+    /// Power the tile powers are raised to before comparing neighbouring tiles for monotonicity.
+    pub mono_power:    f64,
+    /// Penalty per unit of the sum of all tile powers raised to `sum_power`.
+    pub sum_weight:    f64,
+    /// Power each tile power is raised to before summing them.
+    pub sum_power:     f64,
+    /// Penalty per unit of difference between neighbouring non-empty tiles.
+    pub smooth_weight: f64
+}
+
+/// The names of the fields in `HeuristicParams` as accepted by `HeuristicParams::set`.
+pub static PARAM_NAMES: [&str; 7] = [
+    "empty_weight", "merge_weight", "mono_weight", "mono_power", "sum_weight", "sum_power", "smooth_weight"
+];
+
+impl Default for HeuristicParams {
+    fn default() -> Self {
+        HeuristicParams {
+            empty_weight:  270.0,
+            merge_weight:  1400.0,
+            mono_weight:   47.0,
+            mono_power:    4.0,
+            sum_weight:    11.0,
+            sum_power:     3.5,
+            smooth_weight: 0.0
+        }
+    }
+}
+
+impl HeuristicParams {
+    /// Returns the value of the field called `name`, see `tfe::heuristic::PARAM_NAMES`.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        match name {
+            "empty_weight"  => Some(self.empty_weight),
+            "merge_weight"  => Some(self.merge_weight),
+            "mono_weight"   => Some(self.mono_weight),
+            "mono_power"    => Some(self.mono_power),
+            "sum_weight"    => Some(self.sum_weight),
+            "sum_power"     => Some(self.sum_power),
+            "smooth_weight" => Some(self.smooth_weight),
+            _               => None
+        }
+    }
+
+    /// Returns a mutable reference to the field called `name`, see `tfe::heuristic::PARAM_NAMES`.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "empty_weight"  => Some(&mut self.empty_weight),
+            "merge_weight"  => Some(&mut self.merge_weight),
+            "mono_weight"   => Some(&mut self.mono_weight),
+            "mono_power"    => Some(&mut self.mono_power),
+            "sum_weight"    => Some(&mut self.sum_weight),
+            "sum_power"     => Some(&mut self.sum_power),
+            "smooth_weight" => Some(&mut self.smooth_weight),
+            _               => None
+        }
+    }
+
+    /// Sets the field called `name` to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::heuristic::HeuristicParams;
+    ///
+    /// let mut params = HeuristicParams::default();
+    ///
+    /// assert!(params.set("mono_weight", 50.0).is_ok());
+    /// assert!(params.set("unknown", 1.0).is_err());
+    /// assert_eq!(params.mono_weight, 50.0);
+    /// ```
+    pub fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match self.get_mut(name) {
+            Some(field) => { *field = value; Ok(()) },
+            None        => Err(format!("unknown weight: {}", name))
+        }
+    }
+
+    /// Returns the default parameters overridden by every `key = value` pair in `input`.
+    ///
+    /// Pairs are separated by newlines or commas and keys are separated from values by `=` or
+    /// `:`. This means that `input` can be a flat TOML file, a flat JSON object or a list such as
+    /// `merge_weight=700,empty_weight=300`. Comments starting with `#` and `[section]` headers
+    /// are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::heuristic::HeuristicParams;
+    ///
+    /// let toml = HeuristicParams::parse("# weights\nmerge_weight = 700\nsum_power = 3.0\n").unwrap();
+    /// let json = HeuristicParams::parse("{ \"merge_weight\": 700, \"sum_power\": 3.0 }").unwrap();
+    /// let list = HeuristicParams::parse("merge_weight=700,sum_power=3").unwrap();
+    ///
+    /// assert_eq!(toml, json);
+    /// assert_eq!(json, list);
+    /// assert!(HeuristicParams::parse("merge_weight = lots").is_err());
+    /// ```
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut params = Self::default();
+
+        for entry in input.split(&['\n', ','][..]) {
+            let entry = entry.split('#').next().unwrap_or("").trim().trim_matches(|c| c == '{' || c == '}').trim();
+
+            if entry.is_empty() || entry.starts_with('[') { continue }
+
+            let mut parts = entry.splitn(2, &['=', ':'][..]);
+            let key       = parts.next().unwrap_or("").trim().trim_matches('"');
+            let value     = parts.next().ok_or_else(|| format!("expected `key = value`, got: {}", entry))?.trim();
+            let value     = value.parse::<f64>().map_err(|_| format!("invalid value for {}: {}", key, value))?;
+
+            params.set(key, value)?;
+        }
+
+        Ok(params)
+    }
+
+    /// Like `tfe::heuristic::HeuristicParams::parse` but reads the parameters from `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path  = path.as_ref();
+        let input = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        Self::parse(&input)
+    }
+}
+
+impl fmt::Display for HeuristicParams {
+    /// Formats the parameters as TOML which can be read back using `HeuristicParams::parse`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for name in PARAM_NAMES.iter() {
+            writeln!(f, "{} = {:?}", name, self.get(name).unwrap_or(0.0))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Struct that contains the unweighted value of each heuristic term.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Terms {
+    pub empty:        f64,
+    pub merges:       f64,
+    /// The smaller of the increases and the decreases between neighbouring tiles, each summed
+    /// over the row after raising the tile powers to `mono_power`. A row whose tiles only
+    /// increase or only decrease has a monotonicity of `0`.
+    ///
+    /// Earlier releases summed the signed differences instead. Those cancel out to the difference
+    /// between the first and last tile of a row, which rewarded any row whose outer tiles differ
+    /// regardless of the order of the tiles in between.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::heuristic::HEURISTIC;
+    ///
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 8 | 4 | 2 | 4 |
+    ///
+    /// assert_eq!(HEURISTIC.terms(0x0000_0000_0000_3212).monotonicity, 2f64.powf(4.0) - 1.0);
+    /// assert_eq!(HEURISTIC.terms(0x0000_0000_0000_3211).monotonicity, 0.0);
+    /// ```
+    pub monotonicity: f64,
+    pub sum:          f64,
+    pub smoothness:   f64
+}
+
+impl Add for Terms {
+    type Output = Terms;
+
+    fn add(self, other: Terms) -> Terms {
+        Terms {
+            empty:        self.empty        + other.empty,
+            merges:       self.merges       + other.merges,
+            monotonicity: self.monotonicity + other.monotonicity,
+            sum:          self.sum          + other.sum,
+            smoothness:   self.smoothness   + other.smoothness
+        }
+    }
+}
+
+/// Struct that contains the heuristic terms and weighted score for every possible row.
+pub struct Heuristic {
+    pub params: HeuristicParams,
+    pub terms:  Vec<Terms>,
+    pub scores: Vec<f64>
+}
+
+lazy_static! {
+    /// The `Heuristic` using the default weights, built once on first use.
+    /// It is shared by every agent that does not bring its own heuristic.
+    pub static ref HEURISTIC: Arc<Heuristic> = Arc::new(Heuristic::new());
+}
+
+impl Default for Heuristic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heuristic {
+    /// Constructs a new `tfe::heuristic::Heuristic` using the default `HeuristicParams`.
+    pub fn new() -> Self {
+        Self::with_params(&HeuristicParams::default())
+    }
+
+    /// Constructs a new `tfe::heuristic::Heuristic` by scoring every possible row using `params`.
+    pub fn with_params(params: &HeuristicParams) -> Self {
+        let sum_power  = params.sum_power;
+        let mono_power = params.mono_power;
+
+        let mut terms  = vec![Terms::default(); 65536];
+        let mut scores = vec![0f64; 65536];
+
+        for row in 0 .. 65536 {
+            // break row into cells
+            let line = [
+                ((row >>  0) & 0xF) as f64,
+                ((row >>  4) & 0xF) as f64,
+                ((row >>  8) & 0xF) as f64,
+                ((row >> 12) & 0xF) as f64
+            ];
+
+            let mut prev   = 0.0;
+            let mut sum    = 0.0;
+            let mut empty  = 0.0;
+            let mut merges = 0.0;
+            let mut smooth = 0.0;
+            let mut mono_l = 0.0;
+            let mut mono_r = 0.0;
+            let mut last   = 0.0;
+
+            for (i, &tile_pow) in line.iter().enumerate() {
+                sum += tile_pow.powf(sum_power);
+
+                // a row is monotonic if its tiles only increase or only decrease.
+                // the cost of the cheapest of both directions is used as penalty.
+                if i > 0 {
+                    let prev_pow = line[i - 1];
+
+                    if prev_pow > tile_pow {
+                        mono_l += prev_pow.powf(mono_power) - tile_pow.powf(mono_power);
+                    } else {
+                        mono_r += tile_pow.powf(mono_power) - prev_pow.powf(mono_power);
+                    }
+                }
+
+                if tile_pow == 0.0 {
+                    empty += 1.0;
+                    continue;
+                }
+
+                // smoothness compares non-empty tiles, skipping over empty tiles in between.
+                if last != 0.0 { smooth += (tile_pow - last).abs() }
+                last = tile_pow;
+
+                if tile_pow == prev {
+                    merges += 1.0;
+                    prev    = 0.0;
+                    continue;
+                }
+
+                prev = tile_pow;
+            }
+
+            let row_terms = Terms {
+                empty,
+                merges,
+                monotonicity: if mono_l < mono_r { mono_l } else { mono_r },
+                sum,
+                smoothness:   smooth
+            };
+
+            terms[row as usize]  = row_terms;
+            scores[row as usize] =
+                params.empty_weight  * row_terms.empty        +
+                params.merge_weight  * row_terms.merges       -
+                params.mono_weight   * row_terms.monotonicity -
+                params.sum_weight    * row_terms.sum          -
+                params.smooth_weight * row_terms.smoothness;
+        }
+
+        Heuristic { params: params.clone(), terms, scores }
+    }
+
+    /// Returns the heuristic score of `board`, the sum of the scores of its rows and columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::heuristic::HEURISTIC;
+    ///
+    /// // an empty board beats a board with a single tile
+    /// assert!(HEURISTIC.evaluate(0x0000_0000_0000_0000) > HEURISTIC.evaluate(0x0000_0000_0000_0001));
+    ///
+    /// // columns are scored as well, two 2s next to each other score the same
+    /// // horizontally and vertically
+    /// assert_eq!(HEURISTIC.evaluate(0x0000_0000_0000_0011), HEURISTIC.evaluate(0x0000_0000_0001_0001));
+    /// ```
+    pub fn evaluate(&self, board: u64) -> f64 {
+        Game::table_helper(board, &self.scores) + Game::table_helper(Game::transpose(board), &self.scores)
+    }
+
+    /// Returns the unweighted value of each heuristic term for `board`, summed over its rows and
+    /// columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::heuristic::HEURISTIC;
+    ///
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 0 | 0 |
+    /// // | 0 | 0 | 2 | 2 |
+    ///
+    /// let terms = HEURISTIC.terms(0x0000_0000_0000_0011);
+    ///
+    /// // 14 empty tiles, counted once for their row and once for their column
+    /// assert_eq!(terms.empty, 28.0);
+    /// assert_eq!(terms.merges, 1.0);
+    /// assert_eq!(terms.smoothness, 0.0);
+    /// ```
+    pub fn terms(&self, board: u64) -> Terms {
+        Game::table_helper(board, &self.terms) + Game::table_helper(Game::transpose(board), &self.terms)
+    }
+}
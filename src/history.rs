@@ -0,0 +1,223 @@
+use super::rand::Rng;
+
+use super::direction::Direction;
+use super::game::{Game, StepResult};
+use super::rng::GameRng;
+use super::spawn::Spawn;
+
+/// A single move recorded by `tfe::GameHistory`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct HistoryEntry<R = GameRng> {
+    /// The board before the move.
+    pub board: u64,
+    /// The move that was made.
+    pub direction: Direction,
+    /// The tiles that spawned after the move.
+    pub spawns: Vec<Spawn>,
+    /// The score gained by merging tiles during the move.
+    pub score: u64,
+    /// The random number generator of the game before the move.
+    pub rng_before: R,
+    /// The random number generator of the game after the move and its spawns.
+    pub rng_after: R
+}
+
+impl<R> HistoryEntry<R> {
+    /// Returns the board after the move and its spawns.
+    pub fn after(&self) -> u64 {
+        self.spawns.iter().fold(Game::execute(self.board, &self.direction), |board, spawn| board | spawn.tile())
+    }
+
+    /// Returns the number of `4` tiles that spawned after the move.
+    fn fours(&self) -> u64 {
+        self.spawns.iter().filter(|spawn| spawn.value == 2).count() as u64
+    }
+}
+
+/// Records the moves of a `tfe::Game` so that they can be undone and redone.
+///
+/// Every move must be made using `tfe::GameHistory::step` for the history to be able to restore
+/// the game. Undone moves can be redone until a new move is made, which discards them.
+///
+/// Undoing a move also restores the random number generator of the game. Making the same move
+/// again spawns the same tiles, so undo cannot be used to get different spawns. Random number
+/// generators such as `ThreadRng` that cannot be restored to an earlier state do spawn
+/// different tiles.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::{Direction, Game, GameHistory};
+///
+/// let mut game    = Game::from_board(0x0000_0000_0000_1100);
+/// let mut history = GameHistory::new();
+///
+/// history.step(&mut game, &Direction::Left);
+/// let after = game.board;
+///
+/// assert!(history.undo(&mut game));
+/// assert_eq!(game.board, 0x0000_0000_0000_1100);
+/// assert_eq!(game.score, 0);
+///
+/// assert!(history.redo(&mut game));
+/// assert_eq!(game.board, after);
+/// assert_eq!(game.score, 4);
+/// ```
+///
+/// Undoing a move and making it again spawns the same tile:
+///
+/// ```
+/// use tfe::{Direction, Game, GameHistory, GameRng};
+///
+/// for seed in 0 .. 20 {
+///     let mut game    = Game::with_rng(GameRng::new(seed));
+///     let mut history = GameHistory::new();
+///     let direction   = Game::legal_moves(game.board).iter().next().unwrap();
+///
+///     let first = history.step(&mut game, &direction);
+///     history.undo(&mut game);
+///
+///     assert_eq!(history.step(&mut game, &direction), first);
+/// }
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameHistory<R = GameRng> {
+    entries:  Vec<HistoryEntry<R>>,
+    position: usize
+}
+
+impl<R> Default for GameHistory<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> GameHistory<R> {
+    /// Constructs an empty `tfe::GameHistory`.
+    pub fn new() -> Self {
+        GameHistory { entries: vec![], position: 0 }
+    }
+
+    /// Constructs a `tfe::GameHistory` from previously recorded `entries` of which the first
+    /// `position` are applied. `position` is limited to the number of entries.
+    pub fn with_entries(entries: Vec<HistoryEntry<R>>, position: usize) -> Self {
+        let position = position.min(entries.len());
+
+        GameHistory { entries, position }
+    }
+
+    /// Returns every recorded move, including moves that were undone.
+    pub fn entries(&self) -> &[HistoryEntry<R>] {
+        &self.entries
+    }
+
+    /// Returns the number of moves that are currently applied.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of recorded moves, including moves that were undone.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no moves have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<R: Rng + Clone> GameHistory<R> {
+    /// Makes a move in `game` using `tfe::Game::step` and records it if it changed the board.
+    /// Any moves that were undone are discarded.
+    pub fn step(&mut self, game: &mut Game<R>, direction: &Direction) -> StepResult {
+        let board      = game.board;
+        let rng_before = game.rng().clone();
+        let result     = game.step(direction);
+
+        if result.moved {
+            self.entries.truncate(self.position);
+            self.entries.push(HistoryEntry {
+                board,
+                direction:  *direction,
+                spawns:     result.spawns(),
+                score:      result.score,
+                rng_before,
+                rng_after:  game.rng().clone()
+            });
+            self.position += 1;
+        }
+
+        result
+    }
+
+    /// Restores `game`, including its random number generator, to the state before the last
+    /// move. Returns `false` if there is no move to undo.
+    pub fn undo(&mut self, game: &mut Game<R>) -> bool {
+        if self.position == 0 { return false }
+
+        self.position -= 1;
+
+        let entry = &self.entries[self.position];
+
+        game.board      = entry.board;
+        game.score     -= entry.score;
+        game.moves     -= 1;
+        game.fours     -= entry.fours();
+        game.last_spawn = self.position.checked_sub(1).and_then(|i| self.entries[i].spawns.last().cloned());
+        *game.rng()     = entry.rng_before.clone();
+
+        true
+    }
+
+    /// Makes the last undone move again in `game`, including the tiles that spawned after it.
+    /// Returns `false` if there is no move to redo.
+    pub fn redo(&mut self, game: &mut Game<R>) -> bool {
+        if self.position == self.entries.len() { return false }
+
+        let entry = &self.entries[self.position];
+
+        game.board      = entry.after();
+        game.score     += entry.score;
+        game.moves     += 1;
+        game.fours     += entry.fours();
+        game.last_spawn = entry.spawns.last().cloned();
+        *game.rng()     = entry.rng_after.clone();
+
+        self.position += 1;
+
+        true
+    }
+
+    /// Undoes or redoes moves until `game` is in the state after `position` moves.
+    /// Returns `false` if `position` is larger than the number of recorded moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Direction, Game, GameHistory, GameRng};
+    ///
+    /// let mut game    = Game::with_rng(GameRng::new(1));
+    /// let mut history = GameHistory::new();
+    ///
+    /// for direction in &[Direction::Left, Direction::Down, Direction::Right] {
+    ///     history.step(&mut game, direction);
+    /// }
+    ///
+    /// let start = history.entries()[0].board;
+    ///
+    /// assert!(history.jump_to(&mut game, 0));
+    /// assert_eq!(game.board, start);
+    ///
+    /// assert!(history.jump_to(&mut game, history.len()));
+    /// assert!(!history.jump_to(&mut game, history.len() + 1));
+    /// ```
+    pub fn jump_to(&mut self, game: &mut Game<R>, position: usize) -> bool {
+        if position > self.entries.len() { return false }
+
+        while self.position > position { self.undo(game); }
+        while self.position < position { self.redo(game); }
+
+        true
+    }
+}
@@ -0,0 +1,25 @@
+//! Helpers shared by the binary formats of the crate and the binary, see `tfe::weights` and
+//! `tfe::replay`.
+//!
+//! ```
+//! use tfe::io::read_bytes;
+//!
+//! let mut bytes = &[1, 2, 3][..];
+//!
+//! assert_eq!(read_bytes::<_, 2>(&mut bytes).unwrap(), [1, 2]);
+//! assert!(read_bytes::<_, 2>(&mut bytes).is_err());
+//! ```
+
+use std::io::{self, Read};
+
+/// Returns an `io::Error` of kind `InvalidData` with `message`.
+pub fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads exactly `N` bytes from `reader`.
+pub fn read_bytes<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buffer = [0; N];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
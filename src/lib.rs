@@ -5,8 +5,8 @@
 //!
 //! The board itself is encoded as a `u64`. This means that each tile has 4 bits (64 / 16 = 4) to store its
 //! value. Since the maximum value of setting all four bits to 1 is `15` we cannot use it to
-//! display the value directly. Instead we use these 4 bits as the power value: `2 << 15 = 65536`, `2
-//! << 14 = 32768`, `2 << 13 = 16384`, `2 << 12 = 8192`, etc...
+//! display the value directly. Instead we use these 4 bits as the power value: `1 << 15 = 32768`, `1
+//! << 14 = 16384`, `1 << 13 = 8192`, `1 << 12 = 4096`, etc...
 //!
 //! A simple way to play the game automatically is to use `tfe::Game::play`:
 //!
@@ -15,14 +15,17 @@
 //! use tfe::{Game, Direction};
 //!
 //! let game = Game::play(|board, failed| Direction::sample_without(failed));
-//! println!("score: {:<6} board hex: {:016x}", Game::score(game.board), game.board);
+//! println!("score: {:<6} board hex: {:016x}", game.score, game.board);
 //! ```
 //!
 //! The `play` method takes a closure that accepts a `board: u64` and `failed: &Vec<Direction>` as
-//! parameters and returns a `Direction` to move in.
+//! parameters and returns a `Direction` to move in. Agents that need more information about the
+//! game or want to keep state between moves implement `tfe::Agent` and are played using
+//! `tfe::Game::run`.
 //!
-//! The game will terminate automatically if each distinct move has been attempted and failed
-//! without any successfull move in between the failed moves.
+//! The game will terminate automatically once no move can change the board anymore.
+//! Use `tfe::Game::legal_moves` and `tfe::Game::is_over` to find out which moves are possible
+//! without having to try them first.
 //!
 //! ---
 //!
@@ -31,15 +34,35 @@
 //!  - https://github.com/nneonneo/2048-ai/blob/master/2048.cpp
 //!  - https://stackoverflow.com/questions/22342854/what-is-the-optimal-algorithm-for-the-game-2048
 
-#[allow(dead_code)]
+// shifts by 0 are kept on purpose so that table lookups for each row line up.
+#![allow(clippy::identity_op)]
 
 #[macro_use]
 extern crate lazy_static;
 extern crate rand;
 
+mod agent;
 mod direction;
 mod game;
+mod history;
 
-pub use direction::Direction;
-pub use game::Game;
+pub mod agents;
+pub mod ai;
+pub mod heuristic;
+pub mod io;
+pub mod mcts;
+pub mod replay;
+pub mod ntuple;
+pub mod rollout;
+pub mod sim;
+pub mod weights;
+mod rng;
+mod spawn;
+
+pub use agent::{Agent, GameState, GameEnd, RolloutPolicy, RandomRollout, ILLEGAL_MOVE_LIMIT};
+pub use direction::{Direction, DirectionSet};
+pub use game::{Game, StepResult};
+pub use history::{GameHistory, HistoryEntry};
+pub use rng::GameRng;
+pub use spawn::{SpawnRules, Spawn};
 
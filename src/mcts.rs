@@ -0,0 +1,233 @@
+//! Monte Carlo tree search agent with chance nodes for tile spawns.
+//!
+//! The tree alternates between decision nodes, where the agent picks a move, and chance nodes,
+//! where tiles spawn. Decision nodes select moves using UCT, chance nodes sample the tiles that
+//! spawn after a move according to the `tfe::SpawnRules`. New nodes are scored by a rollout
+//! played by a `tfe::RolloutPolicy`, the reward of an iteration is the score gained from the
+//! root onwards.
+
+use std::time::{Duration, Instant};
+
+use super::rand::{thread_rng, Rng};
+
+use super::agent::{Agent, GameState, RandomRollout, RolloutPolicy};
+use super::direction::Direction;
+use super::game::Game;
+use super::rng::GameRng;
+use super::rollout::playout;
+use super::spawn::SpawnRules;
+
+/// How much work `tfe::mcts::Mcts` may do per move.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Budget {
+    /// Run a fixed number of iterations per move, at least one iteration is always run.
+    Iterations(u32),
+    /// Run iterations until the time per move is used up.
+    Time(Duration)
+}
+
+/// A node in the search tree. Decision nodes store the board before a move, chance nodes store
+/// the board after a move and before a tile spawns.
+struct Node {
+    board:    u64,
+    chance:   bool,
+    /// Merge score gained by the move leading to this node, `0` for decision nodes.
+    reward:   u64,
+    visits:   u32,
+    total:    f64,
+    /// Children keyed by `Direction::bit` for decision nodes and by spawned tiles for chance nodes.
+    children: Vec<(u64, usize)>
+}
+
+impl Node {
+    fn new(board: u64, chance: bool, reward: u64) -> Self {
+        Node { board, chance, reward, visits: 0, total: 0.0, children: vec![] }
+    }
+}
+
+/// Agent that performs a Monte Carlo tree search for every move.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::{Agent, Direction, Game};
+/// use tfe::mcts::{Budget, Mcts};
+///
+/// let mut agent = Mcts::new(Budget::Iterations(50));
+/// agent.set_seed(7);
+///
+/// let state = Game::from_board(0x0000_0000_0000_1234).state();
+///
+/// assert_eq!(agent.choose(&state), Direction::Up);
+///
+/// // even without a budget a legal move is chosen
+/// assert_eq!(Mcts::new(Budget::Iterations(0)).choose(&state), Direction::Up);
+/// ```
+pub struct Mcts<P = RandomRollout> {
+    /// The amount of work done per move.
+    pub budget: Budget,
+    /// UCT exploration constant, rewards are normalized to `0 ..= 1` before it is applied.
+    pub exploration: f64,
+    /// Maximum number of moves per rollout, `None` plays rollouts until the game is over.
+    pub rollout_depth: Option<u32>,
+    /// The rules used to spawn tiles in chance nodes and rollouts.
+    pub rules: SpawnRules,
+
+    policy: P,
+    rng:    GameRng,
+    nodes:  Vec<Node>,
+    best:   f64
+}
+
+impl Mcts {
+    /// Constructs a new `tfe::mcts::Mcts` that uses random rollouts.
+    pub fn new(budget: Budget) -> Self {
+        Self::with_policy(budget, RandomRollout)
+    }
+}
+
+impl<P: RolloutPolicy> Mcts<P> {
+    /// Constructs a new `tfe::mcts::Mcts` that uses `policy` to play rollouts.
+    pub fn with_policy(budget: Budget, policy: P) -> Self {
+        Mcts {
+            budget,
+            exploration:   0.5,
+            rollout_depth: None,
+            rules:         SpawnRules::default(),
+            policy,
+            rng:           GameRng::new(thread_rng().gen()),
+            nodes:         vec![],
+            best:          1.0
+        }
+    }
+
+    /// Reseeds the random number generator used for chance nodes and rollouts.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = GameRng::new(seed);
+    }
+
+    /// Returns the number of visits and average reward of each legal move on `board` after
+    /// searching it using the full budget.
+    pub fn search(&mut self, board: u64) -> Vec<(Direction, u32, f64)> {
+        let started    = Instant::now();
+        let mut rounds = 0;
+
+        self.nodes.clear();
+        self.nodes.push(Node::new(board, false, 0));
+        self.best = 1.0;
+
+        loop {
+            match self.budget {
+                Budget::Iterations(n) => if rounds >= n.max(1) { break },
+                Budget::Time(limit)   => if rounds > 0 && started.elapsed() >= limit { break }
+            }
+
+            self.iterate();
+            rounds += 1;
+        }
+
+        self.nodes[0].children.iter().map(|&(bit, child)| {
+            let node      = &self.nodes[child];
+            let direction = Game::legal_moves(board).iter().find(|d| d.bit() as u64 == bit).unwrap();
+
+            (direction, node.visits, if node.visits > 0 { node.total / node.visits as f64 } else { 0.0 })
+        }).collect()
+    }
+
+    /// Runs a single iteration: selection, expansion, rollout and backpropagation.
+    fn iterate(&mut self) {
+        let mut path   = vec![0];
+        let mut node   = 0;
+        let mut reward = 0;
+
+        loop {
+            if self.nodes[node].chance {
+                let board = self.nodes[node].board;
+                let tile  = Game::spawn_tiles_with(board, &self.rules, &mut self.rng);
+                let found = self.nodes[node].children.iter().find(|&&(key, _)| key == tile).map(|&(_, c)| c);
+
+                match found {
+                    Some(child) => {
+                        node = child;
+                        path.push(node);
+                        if self.nodes[node].visits == 0 { break }
+                    },
+                    None => {
+                        let child = self.nodes.len();
+
+                        self.nodes.push(Node::new(board | tile, false, 0));
+                        self.nodes[node].children.push((tile, child));
+                        path.push(child);
+                        node = child;
+                        break;
+                    }
+                }
+            } else {
+                let board = self.nodes[node].board;
+
+                if Game::is_over(board) { break }
+
+                if self.nodes[node].children.is_empty() {
+                    for direction in Game::legal_moves(board).iter() {
+                        let (after, score) = Game::execute_scored(board, &direction);
+                        let child          = self.nodes.len();
+
+                        self.nodes.push(Node::new(after, true, score));
+                        self.nodes[node].children.push((direction.bit() as u64, child));
+                    }
+                }
+
+                node    = self.select(node);
+                reward += self.nodes[node].reward;
+                path.push(node);
+            }
+        }
+
+        let board = self.nodes[node].board;
+        let total = (reward + playout(board, &mut self.policy, &self.rules, &mut self.rng, self.rollout_depth).0) as f64;
+
+        if total > self.best { self.best = total }
+
+        for index in path {
+            self.nodes[index].visits += 1;
+            self.nodes[index].total  += total;
+        }
+    }
+
+    /// Returns the child of decision node `node` with the highest UCT value.
+    /// Unvisited children are always selected first.
+    fn select(&self, node: usize) -> usize {
+        let parent  = &self.nodes[node];
+        let log_n   = (parent.visits.max(1) as f64).ln();
+        let mut top = (parent.children[0].1, f64::MIN);
+
+        for &(_, child) in &parent.children {
+            let stats = &self.nodes[child];
+
+            if stats.visits == 0 { return child }
+
+            let mean  = stats.total / stats.visits as f64 / self.best;
+            let value = mean + self.exploration * (log_n / stats.visits as f64).sqrt();
+
+            if value > top.1 { top = (child, value) }
+        }
+
+        top.0
+    }
+}
+
+impl<P: RolloutPolicy> Agent for Mcts<P> {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        let results = self.search(state.board);
+
+        results.iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(a.2.partial_cmp(&b.2).unwrap()))
+            .map(|result| result.0)
+            .or_else(|| state.legal.iter().next())
+            .unwrap_or(Direction::Left)
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.set_seed(seed);
+    }
+}
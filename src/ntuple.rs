@@ -0,0 +1,244 @@
+//! N-tuple network agent trained using temporal difference learning.
+//!
+//! An n-tuple network estimates the value of a board as the sum of weights looked up by the
+//! tiles found at a few fixed groups of cells, the tuples. Each tuple is also looked up in all 8
+//! rotations and reflections of the board while sharing the same weights.
+//!
+//! Training is done by self-play using TD(0) on afterstates, the boards right after a move and
+//! before a tile spawns. Every move the agent picks the move with the highest merge score plus
+//! value of the afterstate, and moves the value of its previous afterstate towards that estimate.
+//!
+//! ```
+//! use tfe::{Game, GameRng};
+//! use tfe::ntuple::{NTupleNetwork, SMALL_PATTERNS};
+//!
+//! let mut network = NTupleNetwork::new(&SMALL_PATTERNS);
+//!
+//! for seed in 0 .. 10 {
+//!     let mut game = Game::with_rng(GameRng::new(seed));
+//!     network.train_episode(&mut game, 0.1);
+//! }
+//!
+//! let mut game = Game::new();
+//! game.run(&mut network);
+//! ```
+//!
+//! references:
+//!  - Szubert & Jaśkowski, Temporal Difference Learning of N-Tuple Networks for the Game 2048
+//!  - Yeh et al., Multi-Stage Temporal Difference Learning for 2048
+
+use std::io;
+use std::path::Path;
+
+use super::rand::Rng;
+
+use super::agent::{Agent, GameState};
+use super::direction::Direction;
+use super::game::Game;
+use super::io::invalid;
+use super::weights::{WeightFile, table_len};
+
+/// Two straight and two square 4-tuples, small and fast to train.
+pub static SMALL_PATTERNS: [&[u8]; 4] = [
+    &[0, 1, 2, 3],
+    &[4, 5, 6, 7],
+    &[0, 1, 4, 5],
+    &[1, 2, 5, 6]
+];
+
+/// Four 6-tuples as used by most strong 2048 agents. Requires 256MB of weights.
+pub static LARGE_PATTERNS: [&[u8]; 4] = [
+    &[0, 1, 2, 3, 4, 5],
+    &[4, 5, 6, 7, 8, 9],
+    &[0, 1, 2, 4, 5, 6],
+    &[4, 5, 6, 8, 9, 10]
+];
+
+/// Returns `cell` transformed by symmetry `symmetry`, one of the 8 rotations and reflections of
+/// the board. Cells are numbered `0 .. 16`, cell `i` is stored `i * 4` bits from the right.
+fn transform(cell: u8, symmetry: u8) -> u8 {
+    let (mut row, mut col) = (cell / 4, cell % 4);
+
+    for _ in 0 .. symmetry % 4 {
+        let rotated = (col, 3 - row);
+        row = rotated.0;
+        col = rotated.1;
+    }
+
+    if symmetry >= 4 { col = 3 - col }
+
+    row * 4 + col
+}
+
+/// N-tuple network that estimates the value of afterstates.
+#[derive(Clone)]
+pub struct NTupleNetwork {
+    patterns: Vec<Vec<u8>>,
+    features: Vec<Vec<Vec<u8>>>,
+    weights:  Vec<Vec<f32>>
+}
+
+impl NTupleNetwork {
+    /// Constructs a new `tfe::ntuple::NTupleNetwork` with all weights set to `0`.
+    /// Each pattern is a list of cells, see `tfe::ntuple::SMALL_PATTERNS`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a pattern contains a cell outside of the board or is longer than 6 cells.
+    pub fn new(patterns: &[&[u8]]) -> Self {
+        let patterns: Vec<Vec<u8>> = patterns.iter().map(|pattern| pattern.to_vec()).collect();
+        let weights = patterns.iter().map(|pattern| vec![0.0; table_len(4, pattern.len()).unwrap_or(0)]).collect();
+
+        Self::with_weights(patterns, weights).unwrap_or_else(|err| panic!("invalid patterns: {}", err))
+    }
+
+    /// Constructs a `tfe::ntuple::NTupleNetwork` from `patterns` and their `weights`.
+    /// Every pattern of `n` cells needs `16^n` weights, fails when it has any other number of
+    /// weights or contains a cell outside of the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::ntuple::NTupleNetwork;
+    ///
+    /// assert!(NTupleNetwork::with_weights(vec![vec![0, 1]], vec![vec![0.0; 256]]).is_ok());
+    /// assert!(NTupleNetwork::with_weights(vec![vec![0, 1]], vec![vec![0.0; 16]]).is_err());
+    /// assert!(NTupleNetwork::with_weights(vec![vec![0, 16]], vec![vec![0.0; 256]]).is_err());
+    /// ```
+    pub fn with_weights(patterns: Vec<Vec<u8>>, weights: Vec<Vec<f32>>) -> io::Result<Self> {
+        if patterns.len() != weights.len() {
+            return Err(invalid("every pattern needs exactly one list of weights"));
+        }
+
+        for (pattern, weights) in patterns.iter().zip(&weights) {
+            if pattern.iter().any(|&cell| cell > 15) {
+                return Err(invalid("pattern contains a cell outside of the board"));
+            }
+
+            if weights.len() != table_len(4, pattern.len())? {
+                return Err(invalid("pattern has the wrong number of weights"));
+            }
+        }
+
+        let features = patterns.iter()
+            .map(|pattern| (0 .. 8).map(|s| pattern.iter().map(|&cell| transform(cell, s)).collect()).collect())
+            .collect();
+
+        Ok(NTupleNetwork { patterns, features, weights })
+    }
+
+    /// Returns the patterns of this network.
+    pub fn patterns(&self) -> &[Vec<u8>] {
+        &self.patterns
+    }
+
+    /// Returns the weights of each pattern of this network.
+    pub fn weights(&self) -> &[Vec<f32>] {
+        &self.weights
+    }
+
+    /// Writes the patterns and weights of this network to `path` as a `tfe::weights::WeightFile`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        WeightFile { tile_bits: 4, patterns: self.patterns.clone(), weights: self.weights.clone() }.save(path)
+    }
+
+    /// Reads a network from the `tfe::weights::WeightFile` at `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::ntuple::{NTupleNetwork, SMALL_PATTERNS};
+    ///
+    /// let path    = std::env::temp_dir().join("tfe-ntuple-doctest.bin");
+    /// let network = NTupleNetwork::new(&SMALL_PATTERNS);
+    ///
+    /// network.save(&path).unwrap();
+    ///
+    /// let loaded = NTupleNetwork::load(&path).unwrap();
+    /// assert_eq!(loaded.patterns(), network.patterns());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_weight_file(WeightFile::load(path)?)
+    }
+
+    /// Constructs a network from `file`, which must use 4 bits per tile and contain only cells
+    /// that exist on the board.
+    pub fn from_weight_file(file: WeightFile) -> io::Result<Self> {
+        if file.tile_bits != 4 { return Err(invalid("n-tuple networks require 4 bits per tile")) }
+
+        Self::with_weights(file.patterns, file.weights)
+    }
+
+    /// Returns the index into the weights of `cells` on `board`.
+    fn index(board: u64, cells: &[u8]) -> usize {
+        cells.iter().enumerate().fold(0, |index, (i, &cell)| {
+            index | ((((board >> (cell * 4)) & 0xF) as usize) << (4 * i))
+        })
+    }
+
+    /// Returns the estimated value of `board`.
+    pub fn value(&self, board: u64) -> f32 {
+        let mut value = 0.0;
+
+        for (features, weights) in self.features.iter().zip(&self.weights) {
+            for cells in features { value += weights[Self::index(board, cells)] }
+        }
+
+        value
+    }
+
+    /// Adds `delta` to the value of `board`, spread evenly over every feature.
+    pub fn update(&mut self, board: u64, delta: f32) {
+        let count = self.features.len() * 8;
+        let delta = delta / count as f32;
+
+        for (features, weights) in self.features.iter().zip(self.weights.iter_mut()) {
+            for cells in features { weights[Self::index(board, cells)] += delta }
+        }
+    }
+
+    /// Returns the move with the highest merge score plus afterstate value on `board` along
+    /// with that afterstate and its merge score. Returns `None` if no move is possible.
+    pub fn best_move(&self, board: u64) -> Option<(Direction, u64, u64)> {
+        let mut best: Option<(Direction, u64, u64, f32)> = None;
+
+        for direction in Game::legal_moves(board).iter() {
+            let (after, score) = Game::execute_scored(board, &direction);
+            let value          = score as f32 + self.value(after);
+
+            if best.is_none_or(|b| value > b.3) { best = Some((direction, after, score, value)) }
+        }
+
+        best.map(|(direction, after, score, _)| (direction, after, score))
+    }
+
+    /// Plays `game` to completion while learning from it using TD(0) with learning rate `alpha`.
+    ///
+    /// Every move, the value of the previous afterstate is moved towards the merge score plus
+    /// value of the next afterstate. The value of the final afterstate is moved towards `0`.
+    pub fn train_episode<R: Rng>(&mut self, game: &mut Game<R>, alpha: f32) {
+        let mut previous: Option<u64> = None;
+
+        while let Some((direction, after, score)) = self.best_move(game.board) {
+            if let Some(prev) = previous {
+                let error = score as f32 + self.value(after) - self.value(prev);
+                self.update(prev, alpha * error);
+            }
+
+            previous = Some(after);
+            game.step(&direction);
+        }
+
+        if let Some(prev) = previous {
+            let error = -self.value(prev);
+            self.update(prev, alpha * error);
+        }
+    }
+}
+
+impl Agent for NTupleNetwork {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        self.best_move(state.board).map(|(direction, _, _)| direction).unwrap_or(Direction::Left)
+    }
+}
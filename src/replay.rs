@@ -0,0 +1,381 @@
+//! Compact file format used to store and replay games.
+//!
+//! A replay stores the moves of a game packed at 2 bits per move. Games played using a
+//! `tfe::GameRng` only need the seed to reproduce every spawn, other games store their spawns
+//! explicitly. All numbers are stored little-endian:
+//!
+//! | field          | type                   | description                                       |
+//! |----------------|------------------------|---------------------------------------------------|
+//! | magic          | `[u8; 4]`              | always `TFER`                                     |
+//! | version        | `u16`                  | format version, currently `1`                     |
+//! | flags          | `u8`                   | `1`: seeded, `2`: explicit spawns                  |
+//! | seed           | `u64`                  | seed of the `tfe::GameRng`, `0` when not seeded   |
+//! | four chance    | `f64`                  | `SpawnRules::four_probability`                    |
+//! | tiles per move | `u8`                   | `SpawnRules::tiles_per_move`                      |
+//! | start tiles    | `u8`                   | `SpawnRules::start_tiles`                         |
+//! | extra count    | `u8`                   | number of extra outcomes that follow              |
+//! | extra          | `u8`, `f64`            | power and probability of each extra outcome       |
+//! | start board    | `u64`                  | the board before the first move                   |
+//! | final board    | `u64`                  | the board after the last move                     |
+//! | score          | `u64`                  | the score after the last move                     |
+//! | move count     | `u32`                  | number of moves that follow                       |
+//! | moves          | `[u8; (count + 3) / 4]`| 4 moves per byte, lowest bits first               |
+//! | spawns         | `u8`, `[u8; len]`      | only with explicit spawns: per move the number of |
+//! |                |                        | spawns, each spawn is `index << 4 \| power`       |
+//!
+//! Moves are stored as `0`: left, `1`: right, `2`: up and `3`: down.
+//!
+//! ```
+//! use tfe::agents::Greedy;
+//! use tfe::SpawnRules;
+//! use tfe::replay::Replay;
+//!
+//! let replay    = Replay::record(42, SpawnRules::default(), &mut Greedy);
+//! let mut bytes = vec![];
+//!
+//! replay.write(&mut bytes).unwrap();
+//!
+//! let read = Replay::read(&mut &bytes[..]).unwrap();
+//!
+//! assert_eq!(read, replay);
+//! assert_eq!(read.verify(), Ok(()));
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::agent::{Agent, ILLEGAL_MOVE_LIMIT};
+use super::direction::Direction;
+use super::game::{Game, StepResult};
+use super::history::GameHistory;
+use super::io::{invalid, read_bytes};
+use super::rng::GameRng;
+use super::spawn::{Spawn, SpawnRules};
+
+/// The first 4 bytes of every replay file.
+pub const MAGIC: &[u8; 4] = b"TFER";
+
+/// The version of the format written by this crate.
+pub const VERSION: u16 = 1;
+
+/// A recorded game that can be written to and read from a replay file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Replay {
+    /// Seed of the `tfe::GameRng` the game was played with, `None` if its spawns are stored.
+    pub seed: Option<u64>,
+    /// The rules used to spawn tiles.
+    pub rules: SpawnRules,
+    /// The board before the first move.
+    pub start: u64,
+    /// Every move that changed the board.
+    pub moves: Vec<Direction>,
+    /// The tiles spawned after each move, empty for seeded games.
+    pub spawns: Vec<Vec<Spawn>>,
+    /// The board after the last move.
+    pub final_board: u64,
+    /// The score after the last move.
+    pub score: u64
+}
+
+impl Replay {
+    /// Constructs an empty `tfe::replay::Replay` of a game that starts at `start`.
+    /// Games that are not seeded store their spawns explicitly.
+    pub fn new(seed: Option<u64>, rules: SpawnRules, start: u64) -> Self {
+        Replay { seed, rules, start, moves: vec![], spawns: vec![], final_board: start, score: 0 }
+    }
+
+    /// Plays a game seeded with `seed` using `agent` and records it. Like `tfe::Game::run`, the
+    /// agent is stopped after `tfe::ILLEGAL_MOVE_LIMIT` moves in a row that do nothing.
+    pub fn record<A: Agent + ?Sized>(seed: u64, rules: SpawnRules, agent: &mut A) -> Self {
+        let mut game    = Game::with_rng_and_rules(GameRng::new(seed), rules.clone());
+        let mut replay  = Self::new(Some(seed), rules, game.board);
+        let mut illegal = 0;
+
+        agent.reset();
+
+        while !Game::is_over(game.board) && illegal < ILLEGAL_MOVE_LIMIT {
+            let direction = agent.choose(&game.state());
+            let result    = game.step(&direction);
+
+            if result.moved {
+                replay.push(direction, &result);
+                illegal = 0;
+            } else {
+                illegal += 1;
+            }
+        }
+
+        agent.on_game_end(&game.state());
+        replay
+    }
+
+    /// Constructs a replay with explicit spawns from the moves that are currently applied in
+    /// `history`, which started at `start` and was played using `rules`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{Direction, Game, GameHistory, SpawnRules};
+    /// use tfe::replay::Replay;
+    ///
+    /// let mut game    = Game::new();
+    /// let mut history = GameHistory::new();
+    /// let start       = game.board;
+    ///
+    /// for direction in &[Direction::Left, Direction::Up, Direction::Right] {
+    ///     history.step(&mut game, direction);
+    /// }
+    ///
+    /// let replay = Replay::from_history(start, SpawnRules::default(), &history);
+    ///
+    /// assert_eq!(replay.seed, None);
+    /// assert_eq!(replay.final_board, game.board);
+    /// assert_eq!(replay.verify(), Ok(()));
+    /// ```
+    pub fn from_history<R>(start: u64, rules: SpawnRules, history: &GameHistory<R>) -> Self {
+        let mut replay = Self::new(None, rules, start);
+
+        for entry in &history.entries()[.. history.position()] {
+            replay.moves.push(entry.direction);
+            replay.spawns.push(entry.spawns.clone());
+            replay.final_board = entry.after();
+            replay.score      += entry.score;
+        }
+
+        replay
+    }
+
+    /// Records a move and the `result` of making it. Moves that did not change the board are
+    /// ignored.
+    pub fn push(&mut self, direction: Direction, result: &StepResult) {
+        if !result.moved { return }
+
+        self.moves.push(direction);
+        if self.seed.is_none() { self.spawns.push(result.spawns()) }
+
+        self.final_board = Game::execute(self.final_board, &direction) | result.spawned;
+        self.score      += result.score;
+    }
+
+    /// Rebuilds the game and returns the board before the first move followed by the board
+    /// after every move. Fails if a move does not change the board or the spawns do not fit.
+    pub fn boards(&self) -> Result<Vec<u64>, String> {
+        let mut boards = vec![self.start];
+        let mut game   = match self.seed {
+            Some(seed) => {
+                let game = Game::with_rng_and_rules(GameRng::new(seed), self.rules.clone());
+
+                if game.board != self.start { return Err(String::from("start board does not match the seed")) }
+
+                game
+            },
+            None => Game::from_board_with(self.start, GameRng::new(0), self.rules.clone())
+        };
+
+        for (i, direction) in self.moves.iter().enumerate() {
+            if self.seed.is_some() {
+                if !game.step(direction).moved { return Err(format!("move {} does not change the board", i + 1)) }
+            } else {
+                let moved = Game::execute(game.board, direction);
+
+                if moved == game.board { return Err(format!("move {} does not change the board", i + 1)) }
+
+                game.board = moved;
+
+                for spawn in self.spawns.get(i).map(|s| &s[..]).unwrap_or(&[]) {
+                    if (game.board >> (spawn.index * 4)) & 0xF != 0 {
+                        return Err(format!("move {} spawns a tile on an occupied cell", i + 1));
+                    }
+
+                    game.board |= spawn.tile();
+                }
+            }
+
+            boards.push(game.board);
+        }
+
+        Ok(boards)
+    }
+
+    /// Rebuilds the game and checks that it ends at the stored final board.
+    pub fn verify(&self) -> Result<(), String> {
+        let boards = self.boards()?;
+        let last   = boards[boards.len() - 1];
+
+        if last != self.final_board {
+            return Err(format!("final board {:016x} does not match the stored {:016x}", last, self.final_board));
+        }
+
+        Ok(())
+    }
+
+    /// Writes this replay to `writer`.
+    /// Fails without writing anything when `tfe::replay::Replay::read` could not read it back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::replay::Replay;
+    /// use tfe::SpawnRules;
+    ///
+    /// // the tile counts are stored in a single byte
+    /// let rules = SpawnRules { tiles_per_move: 256, ..SpawnRules::default() };
+    ///
+    /// assert!(Replay::new(Some(1), rules, 0).write(&mut vec![]).is_err());
+    /// ```
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let explicit = self.seed.is_none();
+        let rules    = &self.rules;
+
+        rules.validate().map_err(|err| invalid(&err))?;
+
+        if rules.tiles_per_move > u8::MAX as usize || rules.start_tiles > u8::MAX as usize {
+            return Err(invalid("too many tiles spawned at once"));
+        }
+
+        if rules.extra.len() > u8::MAX as usize { return Err(invalid("too many extra spawn powers")) }
+        if self.moves.len() > u32::MAX as usize { return Err(invalid("too many moves")) }
+
+        if explicit && self.spawns.len() != self.moves.len() {
+            return Err(invalid("every move needs exactly one list of spawns"));
+        }
+
+        if explicit {
+            for spawns in &self.spawns {
+                if spawns.len() > u8::MAX as usize { return Err(invalid("too many spawns after a move")) }
+
+                if spawns.iter().any(|spawn| spawn.index > 15 || spawn.value > 15) {
+                    return Err(invalid("spawn does not fit on the board"));
+                }
+            }
+        }
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&[if explicit { 2 } else { 1 }])?;
+        writer.write_all(&self.seed.unwrap_or(0).to_le_bytes())?;
+
+        writer.write_all(&self.rules.four_probability.to_le_bytes())?;
+        writer.write_all(&[self.rules.tiles_per_move as u8, self.rules.start_tiles as u8, self.rules.extra.len() as u8])?;
+
+        for &(power, probability) in &self.rules.extra {
+            writer.write_all(&[power as u8])?;
+            writer.write_all(&probability.to_le_bytes())?;
+        }
+
+        writer.write_all(&self.start.to_le_bytes())?;
+        writer.write_all(&self.final_board.to_le_bytes())?;
+        writer.write_all(&self.score.to_le_bytes())?;
+        writer.write_all(&(self.moves.len() as u32).to_le_bytes())?;
+
+        for chunk in self.moves.chunks(4) {
+            let byte = chunk.iter().enumerate().fold(0u8, |byte, (i, direction)| {
+                byte | (direction.code() << (i * 2))
+            });
+
+            writer.write_all(&[byte])?;
+        }
+
+        if explicit {
+            for spawns in &self.spawns {
+                writer.write_all(&[spawns.len() as u8])?;
+
+                for spawn in spawns {
+                    writer.write_all(&[((spawn.index as u8) << 4) | spawn.value as u8])?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a replay from `reader`, verifying its version and flags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::replay::Replay;
+    /// use tfe::SpawnRules;
+    ///
+    /// let mut bytes = vec![];
+    /// Replay::new(Some(1), SpawnRules::default(), 0).write(&mut bytes).unwrap();
+    ///
+    /// // claim a billion moves without storing any of them
+    /// let len = bytes.len();
+    /// bytes[len - 4 ..].copy_from_slice(&1_000_000_000u32.to_le_bytes());
+    ///
+    /// assert!(Replay::read(&mut &bytes[..]).is_err());
+    /// ```
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        if &read_bytes::<_, 4>(reader)? != MAGIC { return Err(invalid("not a replay file")) }
+
+        let version = u16::from_le_bytes(read_bytes(reader)?);
+
+        if version != VERSION {
+            return Err(invalid(&format!("unsupported replay version: {}", version)));
+        }
+
+        let [flags] = read_bytes::<_, 1>(reader)?;
+        let seed    = u64::from_le_bytes(read_bytes(reader)?);
+
+        if flags != 1 && flags != 2 { return Err(invalid("invalid replay flags")) }
+
+        let four_probability = f64::from_le_bytes(read_bytes(reader)?);
+        let [tiles_per_move, start_tiles, count] = read_bytes::<_, 3>(reader)?;
+        let mut extra = Vec::with_capacity(count as usize);
+
+        for _ in 0 .. count {
+            let [power] = read_bytes::<_, 1>(reader)?;
+            extra.push((power as u64, f64::from_le_bytes(read_bytes(reader)?)));
+        }
+
+        let rules = SpawnRules {
+            four_probability,
+            extra,
+            tiles_per_move: tiles_per_move as usize,
+            start_tiles:    start_tiles as usize
+        };
+
+        rules.validate().map_err(|err| invalid(&err))?;
+
+        let start       = u64::from_le_bytes(read_bytes(reader)?);
+        let final_board = u64::from_le_bytes(read_bytes(reader)?);
+        let score       = u64::from_le_bytes(read_bytes(reader)?);
+        let count       = u32::from_le_bytes(read_bytes(reader)?) as usize;
+        let mut packed  = vec![];
+        let mut spawns  = vec![];
+
+        // the count is not trusted for allocating, moves are only kept once they have been read
+        reader.take(count.div_ceil(4) as u64).read_to_end(&mut packed)?;
+
+        if packed.len() < count.div_ceil(4) { return Err(invalid("replay ends before its last move")) }
+
+        let moves = (0 .. count).map(|i| Direction::from_code((packed[i / 4] >> ((i % 4) * 2)) & 0b11).unwrap()).collect();
+
+        if flags == 2 {
+            for _ in 0 .. count {
+                let [len]     = read_bytes::<_, 1>(reader)?;
+                let mut bytes = vec![0; len as usize];
+
+                reader.read_exact(&mut bytes)?;
+                spawns.push(bytes.iter().map(|&b| Spawn { index: (b >> 4) as usize, value: (b & 0xF) as u64 }).collect());
+            }
+        }
+
+        Ok(Replay { seed: if flags == 1 { Some(seed) } else { None }, rules, start, moves, spawns, final_board, score })
+    }
+
+    /// Writes this replay to `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads a replay from `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+}
@@ -0,0 +1,91 @@
+use super::rand::{Error, RngCore, SeedableRng};
+
+/// A small, seedable random number generator used to make games reproducible.
+///
+/// `GameRng` is a SplitMix64 generator. Its entire state is a single `u64` which makes it cheap
+/// to create one per game and trivial to store alongside a board. Unlike the generators shipped
+/// with `rand`, the sequence produced for a given seed is part of this crate's contract and will
+/// not change between versions or platforms.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rand;
+/// extern crate tfe;
+///
+/// use rand::RngCore;
+/// use tfe::GameRng;
+///
+/// let mut a = GameRng::new(42);
+/// let mut b = GameRng::new(42);
+///
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameRng { state: u64 }
+
+impl GameRng {
+    /// Constructs a new `tfe::GameRng` from `seed`.
+    pub fn new(seed: u64) -> Self {
+        GameRng { state: seed }
+    }
+
+    /// Returns the current internal state.
+    /// A generator constructed using `GameRng::new(rng.state())` continues the exact same
+    /// sequence as `rng`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// extern crate tfe;
+    ///
+    /// use rand::RngCore;
+    /// use tfe::GameRng;
+    ///
+    /// let mut rng = GameRng::new(7);
+    /// rng.next_u64();
+    ///
+    /// let mut copy = GameRng::new(rng.state());
+    /// assert_eq!(rng.next_u64(), copy.next_u64());
+    /// ```
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[.. chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for GameRng {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: [u8; 8]) -> Self {
+        GameRng::new(u64::from_le_bytes(seed))
+    }
+}
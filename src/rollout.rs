@@ -0,0 +1,147 @@
+//! Pure Monte Carlo rollout agent.
+//!
+//! For every legal move, the agent plays a number of playouts from the resulting board and picks
+//! the move with the best average outcome. There is no tree and no heuristic, which makes it a
+//! simple but surprisingly strong baseline.
+
+use super::rand::{thread_rng, Rng};
+
+use super::ai::best;
+use super::agent::{Agent, GameState, RandomRollout, RolloutPolicy};
+use super::direction::Direction;
+use super::game::Game;
+use super::rng::GameRng;
+use super::spawn::SpawnRules;
+
+/// What `tfe::rollout::MonteCarlo` maximizes on average.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Objective {
+    /// The score gained by merging during a playout.
+    Score,
+    /// The number of moves a playout survives.
+    Moves
+}
+
+/// Plays moves chosen by `policy` from `board` until the game is over or `depth` moves are made.
+/// After every move `rules.tiles_per_move` tiles spawn. Returns the score gained and the number of
+/// moves made.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::{GameRng, RandomRollout, SpawnRules};
+/// use tfe::rollout::playout;
+///
+/// let mut rng = GameRng::new(3);
+/// let (score, moves) = playout(0x0000_0000_0000_0011, &mut RandomRollout, &SpawnRules::default(), &mut rng, Some(5));
+///
+/// assert!(score >= 4);
+/// assert_eq!(moves, 5);
+/// ```
+pub fn playout<P: RolloutPolicy + ?Sized>(board: u64, policy: &mut P, rules: &SpawnRules, rng: &mut GameRng, depth: Option<u32>) -> (u64, u32) {
+    let mut board = board;
+    let mut score = 0;
+    let mut moves = 0;
+
+    loop {
+        let legal = Game::legal_moves(board);
+
+        if legal.is_empty() || depth.is_some_and(|depth| moves >= depth) { break }
+
+        let direction       = policy.choose(board, legal, rng);
+        let (after, gained) = Game::execute_scored(board, &direction);
+
+        board  = after | Game::spawn_tiles_with(after, rules, rng);
+        score += gained;
+        moves += 1;
+    }
+
+    (score, moves)
+}
+
+/// Agent that picks the move with the best average result over a number of playouts.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::{Agent, Direction, Game};
+/// use tfe::rollout::MonteCarlo;
+///
+/// let mut agent = MonteCarlo::new(20);
+/// agent.set_seed(1);
+///
+/// let state = Game::from_board(0x0000_0000_0000_1234).state();
+///
+/// assert_eq!(agent.choose(&state), Direction::Up);
+/// ```
+pub struct MonteCarlo<P = RandomRollout> {
+    /// The number of playouts per legal move.
+    pub playouts: u32,
+    /// Maximum number of moves per playout, `None` plays until the game is over.
+    pub depth: Option<u32>,
+    /// What the agent maximizes.
+    pub objective: Objective,
+    /// The rules used to spawn tiles during playouts.
+    pub rules: SpawnRules,
+
+    policy: P,
+    rng:    GameRng
+}
+
+impl MonteCarlo {
+    /// Constructs a new `tfe::rollout::MonteCarlo` that plays `playouts` random playouts per move.
+    pub fn new(playouts: u32) -> Self {
+        Self::with_policy(playouts, RandomRollout)
+    }
+}
+
+impl<P: RolloutPolicy> MonteCarlo<P> {
+    /// Constructs a new `tfe::rollout::MonteCarlo` that uses `policy` to play playouts.
+    pub fn with_policy(playouts: u32, policy: P) -> Self {
+        MonteCarlo {
+            playouts,
+            depth:     None,
+            objective: Objective::Score,
+            rules:     SpawnRules::default(),
+            policy,
+            rng:       GameRng::new(thread_rng().gen())
+        }
+    }
+
+    /// Reseeds the random number generator used for playouts.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = GameRng::new(seed);
+    }
+
+    /// Returns the average result of the playouts for each legal move on `board`.
+    pub fn scores(&mut self, board: u64) -> Vec<(Direction, f64)> {
+        Game::legal_moves(board).iter().map(|direction| {
+            let (after, gained) = Game::execute_scored(board, &direction);
+            let mut total       = 0.0;
+
+            for _ in 0 .. self.playouts {
+                let start          = after | Game::spawn_tiles_with(after, &self.rules, &mut self.rng);
+                let (score, moves) = playout(start, &mut self.policy, &self.rules, &mut self.rng, self.depth);
+
+                total += match self.objective {
+                    Objective::Score => (gained + score) as f64,
+                    Objective::Moves => moves as f64
+                };
+            }
+
+            (direction, total / self.playouts.max(1) as f64)
+        }).collect()
+    }
+}
+
+impl<P: RolloutPolicy> Agent for MonteCarlo<P> {
+    fn choose(&mut self, state: &GameState) -> Direction {
+        let scores = self.scores(state.board);
+
+        if scores.is_empty() { Direction::Left } else { best(&scores) }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.set_seed(seed);
+    }
+}
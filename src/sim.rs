@@ -0,0 +1,223 @@
+//! Plays batches of games spread over multiple threads and collects their results.
+//!
+//! ```
+//! use tfe::agents::Greedy;
+//! use tfe::sim::{run_batch, BatchConfig};
+//!
+//! let config = BatchConfig { count: 10, threads: 4, ..BatchConfig::default() };
+//! let report = run_batch(&config, || Greedy);
+//!
+//! assert_eq!(report.games.len(), 10);
+//! assert!(report.average_score() > 0.0);
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::rand::{random, RngCore};
+
+use super::agent::{Agent, GameEnd};
+use super::game::Game;
+use super::replay::Replay;
+use super::rng::GameRng;
+use super::spawn::SpawnRules;
+
+/// Configuration of a batch of games played by `tfe::sim::run_batch`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BatchConfig {
+    /// The number of games to play.
+    pub count: usize,
+    /// The number of threads to play on, at most one thread per game is used.
+    pub threads: usize,
+    /// The rules used to spawn tiles.
+    pub rules: SpawnRules,
+    /// Record a `tfe::replay::Replay` of every game.
+    pub record: bool,
+    /// Master seed of the batch. When set, every game is seeded using `tfe::sim::game_seed` so
+    /// that the results do not depend on the number of threads. Games use random seeds otherwise.
+    pub seed: Option<u64>
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        BatchConfig { count: 1, threads: 1, rules: SpawnRules::default(), record: false, seed: None }
+    }
+}
+
+/// The result of a single game in a batch.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameRecord {
+    /// Index of the game in the batch, `0 .. count`.
+    pub index: usize,
+    /// Seed of the `tfe::GameRng` the game was played with.
+    pub seed: u64,
+    /// The final board.
+    pub board: u64,
+    /// The final score.
+    pub score: u64,
+    /// The number of moves made.
+    pub moves: u64,
+    /// The value of the highest tile on the final board.
+    pub highest: u64,
+    /// Why the game ended.
+    pub end: GameEnd,
+    /// How long the game took to play.
+    pub duration: Duration,
+    /// The replay of the game if `tfe::sim::BatchConfig::record` is set.
+    pub replay: Option<Replay>
+}
+
+/// The results of a batch of games, returned by `tfe::sim::run_batch`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BatchReport {
+    /// Every game, ordered by index.
+    pub games: Vec<GameRecord>,
+    /// How long the whole batch took to play.
+    pub duration: Duration
+}
+
+impl BatchReport {
+    /// Returns the average score of all games, `0` for an empty batch.
+    pub fn average_score(&self) -> f64 {
+        if self.games.is_empty() { return 0.0 }
+
+        self.games.iter().map(|game| game.score).sum::<u64>() as f64 / self.games.len() as f64
+    }
+
+    /// Returns the game with the highest score, the first one on a tie.
+    pub fn best(&self) -> Option<&GameRecord> {
+        self.games.iter().fold(None, |best: Option<&GameRecord>, game| match best {
+            Some(b) if b.score >= game.score => Some(b),
+            _                                => Some(game)
+        })
+    }
+
+    /// Returns the number of games that reached a tile of at least `tile`.
+    pub fn reached(&self, tile: u64) -> usize {
+        self.games.iter().filter(|game| game.highest >= tile).count()
+    }
+}
+
+/// Returns the seed of game `index` in a batch with master seed `master`.
+///
+/// This is the `index`-th number generated by `tfe::GameRng::new(master)`, computed without
+/// generating the numbers before it.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rand;
+/// extern crate tfe;
+///
+/// use rand::RngCore;
+/// use tfe::GameRng;
+/// use tfe::sim::game_seed;
+///
+/// let mut rng = GameRng::new(42);
+///
+/// assert_eq!(game_seed(42, 0), rng.next_u64());
+/// assert_eq!(game_seed(42, 1), rng.next_u64());
+/// ```
+pub fn game_seed(master: u64, index: usize) -> u64 {
+    GameRng::new(master.wrapping_add((index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))).next_u64()
+}
+
+/// Returns the seed passed to `tfe::Agent::seed` before playing a game seeded with `seed`.
+fn agent_seed(seed: u64) -> u64 {
+    GameRng::new(!seed).next_u64()
+}
+
+/// Plays game `index` of a batch seeded with `seed` using `agent`.
+fn play<A: Agent + ?Sized>(index: usize, seed: u64, config: &BatchConfig, agent: &mut A) -> GameRecord {
+    let started = Instant::now();
+
+    agent.seed(agent_seed(seed));
+
+    let (board, score, moves, end, replay) = if config.record {
+        let replay = Replay::record(seed, config.rules.clone(), agent);
+        let end    = if Game::is_over(replay.final_board) { GameEnd::Over } else { GameEnd::IllegalMoves };
+
+        (replay.final_board, replay.score, replay.moves.len() as u64, end, Some(replay))
+    } else {
+        let mut game = Game::with_rng_and_rules(GameRng::new(seed), config.rules.clone());
+        let end      = game.run(agent);
+
+        (game.board, game.score, game.moves, end, None)
+    };
+
+    GameRecord { index, seed, board, score, moves, highest: Game::highest_tile(board), end, duration: started.elapsed(), replay }
+}
+
+/// Plays game `index` of the batch described by `config` using `agent` and returns its result.
+///
+/// In a batch with a master seed, this plays the exact same game as `tfe::sim::run_batch` did,
+/// which allows a single game to be rerun on its own.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::agents::Random;
+/// use tfe::sim::{run_batch, run_game, BatchConfig};
+///
+/// let config = BatchConfig { count: 4, threads: 2, seed: Some(42), ..BatchConfig::default() };
+/// let report = run_batch(&config, Random::new);
+/// let again  = run_game(&config, 3, &mut Random::new());
+///
+/// assert_eq!(again.board, report.games[3].board);
+/// assert_eq!(again.score, report.games[3].score);
+/// ```
+pub fn run_game<A: Agent + ?Sized>(config: &BatchConfig, index: usize, agent: &mut A) -> GameRecord {
+    let seed = config.seed.map(|master| game_seed(master, index)).unwrap_or_else(random);
+
+    play(index, seed, config, agent)
+}
+
+/// Plays `config.count` games spread over `config.threads` threads and returns their results.
+///
+/// Every thread constructs its own agent using `factory` and keeps taking the next game to play
+/// until all games are played, so any count works with any number of threads. With a master
+/// seed, every game and the agent playing it are seeded from the game's index, so the results are
+/// the same for any number of threads.
+///
+/// # Examples
+///
+/// ```
+/// use tfe::agents::Random;
+/// use tfe::sim::{run_batch, BatchConfig};
+///
+/// let config = BatchConfig { count: 3, threads: 8, ..BatchConfig::default() };
+/// let report = run_batch(&config, Random::new);
+///
+/// let indices: Vec<usize> = report.games.iter().map(|game| game.index).collect();
+/// assert_eq!(indices, vec![0, 1, 2]);
+/// ```
+pub fn run_batch<A, F>(config: &BatchConfig, factory: F) -> BatchReport
+    where A: Agent, F: Fn() -> A + Sync {
+    let started = Instant::now();
+    let next    = AtomicUsize::new(0);
+    let threads = config.threads.clamp(1, config.count.max(1));
+
+    let mut games: Vec<GameRecord> = thread::scope(|scope| {
+        let handles: Vec<_> = (0 .. threads).map(|_| scope.spawn(|| {
+            let mut agent   = factory();
+            let mut results = vec![];
+
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+
+                if index >= config.count { break }
+
+                results.push(run_game(config, index, &mut agent));
+            }
+
+            results
+        })).collect();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    });
+
+    games.sort_by_key(|game| game.index);
+
+    BatchReport { games, duration: started.elapsed() }
+}
@@ -0,0 +1,153 @@
+use super::rand::Rng;
+
+/// Struct that describes which tiles spawn, how often and how many of them.
+///
+/// Tile values are stored as power values, just like on the board: `1` spawns a `2`, `2` spawns
+/// a `4`, `3` spawns an `8`, etc...
+///
+/// `SpawnRules::default()` returns the standard 2048 rules: two starting tiles and a single tile
+/// spawned after every move which is a `2` with 90% chance and a `4` with 10% chance.
+///
+/// # Examples
+///
+/// A harder variant where half of the spawned tiles are `4`s, 5% are `8`s and two tiles spawn
+/// after every move:
+///
+/// ```
+/// use tfe::SpawnRules;
+///
+/// let rules = SpawnRules {
+///     four_probability: 0.5,
+///     extra:            vec![(3, 0.05)],
+///     tiles_per_move:   2,
+///     ..SpawnRules::default()
+/// };
+///
+/// assert_eq!(rules.start_tiles, 2);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpawnRules {
+    /// Probability of spawning a `4` instead of a `2`.
+    pub four_probability: f64,
+    /// Additional `(power, probability)` pairs, e.g. `(3, 0.01)` spawns an `8` 1% of the time.
+    /// These are rolled before `four_probability`, a `2` spawns with the remaining probability.
+    pub extra: Vec<(u64, f64)>,
+    /// The number of tiles spawned after each successful move.
+    pub tiles_per_move: usize,
+    /// The number of tiles on the board when a game starts.
+    pub start_tiles: usize
+}
+
+impl Default for SpawnRules {
+    fn default() -> Self {
+        SpawnRules { four_probability: 0.1, extra: vec![], tiles_per_move: 1, start_tiles: 2 }
+    }
+}
+
+impl SpawnRules {
+    /// Checks that every tile these rules spawn fits in a single cell of the board and that the
+    /// probabilities of the spawned tiles add up to at most `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::SpawnRules;
+    ///
+    /// assert!(SpawnRules::default().validate().is_ok());
+    /// assert!(SpawnRules { extra: vec![(16, 0.1)], ..SpawnRules::default() }.validate().is_err());
+    /// assert!(SpawnRules { extra: vec![(3, 0.95)], ..SpawnRules::default() }.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), String> {
+        let probabilities = self.extra.iter().map(|&(_, p)| p).chain(Some(self.four_probability));
+
+        if let Some(&(power, _)) = self.extra.iter().find(|&&(power, _)| !(1 ..= 15).contains(&power)) {
+            return Err(format!("tile power must be between 1 and 15, got: {}", power));
+        }
+
+        if probabilities.clone().any(|p| !(0.0 ..= 1.0).contains(&p)) {
+            return Err(String::from("tile probabilities must be between 0 and 1"));
+        }
+
+        if probabilities.sum::<f64>() > 1.0 + 1e-9 {
+            return Err(String::from("tile probabilities add up to more than 1"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns a random tile power value drawn from `rng` according to these rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::{GameRng, SpawnRules};
+    ///
+    /// let always_four = SpawnRules { four_probability: 1.0, ..SpawnRules::default() };
+    ///
+    /// assert_eq!(always_four.tile(&mut GameRng::new(0)), 2);
+    /// ```
+    pub fn tile<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        let mut roll = rng.gen::<f64>();
+
+        for &(power, probability) in &self.extra {
+            if roll < probability { return power }
+            roll -= probability;
+        }
+
+        if roll < self.four_probability { 2 } else { 1 }
+    }
+
+    /// Returns every `(power, probability)` pair a spawned tile can have, including the `2` and
+    /// `4` tiles. Pairs with a probability of `0` are omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::SpawnRules;
+    ///
+    /// assert_eq!(SpawnRules::default().outcomes(), vec![(1, 0.9), (2, 0.1)]);
+    /// ```
+    pub fn outcomes(&self) -> Vec<(u64, f64)> {
+        let extra: f64  = self.extra.iter().map(|&(_, p)| p).sum();
+        let mut results = vec![(1, 1.0 - extra - self.four_probability), (2, self.four_probability)];
+
+        results.extend(self.extra.iter().cloned());
+        results.retain(|&(_, p)| p > 0.0);
+        results
+    }
+}
+
+/// A single tile that was spawned onto the board.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Spawn {
+    /// Position of the tile on the board, the tile is stored `index * 4` bits from the right.
+    pub index: usize,
+    /// Power value of the tile, `1` for a `2`, `2` for a `4`, etc...
+    pub value: u64
+}
+
+impl Spawn {
+    /// Returns every non-empty tile in `tiles` as a `Spawn`, lowest index first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::Spawn;
+    ///
+    /// assert_eq!(Spawn::from_tiles(0x0200_0000_0000_0010), vec![
+    ///     Spawn { index:  1, value: 1 },
+    ///     Spawn { index: 14, value: 2 }
+    /// ]);
+    /// ```
+    pub fn from_tiles(tiles: u64) -> Vec<Spawn> {
+        (0 .. 16).filter_map(|index| {
+            let value = (tiles >> (index * 4)) & 0xF;
+            if value == 0 { None } else { Some(Spawn { index, value }) }
+        }).collect()
+    }
+
+    /// Returns this spawn as a board containing only this tile.
+    pub fn tile(&self) -> u64 {
+        self.value << (self.index * 4)
+    }
+}
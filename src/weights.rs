@@ -0,0 +1,194 @@
+//! Binary file format used to store the weights of learned agents.
+//!
+//! All numbers are stored little-endian:
+//!
+//! | field         | type                 | description                                   |
+//! |---------------|----------------------|-----------------------------------------------|
+//! | magic         | `[u8; 4]`            | always `TFEW`                                 |
+//! | version       | `u16`                | format version, currently `1`                 |
+//! | tile bits     | `u8`                 | number of bits used per tile, `4` for `u64`s   |
+//! | pattern count | `u16`                | number of patterns that follow                |
+//! | patterns      | `u8`, `[u8; len]`    | length of each pattern followed by its cells  |
+//! | checksum      | `u32`                | FNV-1a hash of all weight bytes that follow   |
+//! | weights       | `[f32; 2^(bits*len)]`| the weights of each pattern in pattern order  |
+//!
+//! ```
+//! use tfe::weights::WeightFile;
+//!
+//! let file = WeightFile { tile_bits: 4, patterns: vec![vec![0]], weights: vec![vec![1.5; 16]] };
+//! let mut bytes = vec![];
+//!
+//! file.write(&mut bytes).unwrap();
+//!
+//! assert_eq!(WeightFile::read(&mut &bytes[..]).unwrap(), file);
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::io::{invalid, read_bytes};
+
+/// The first 4 bytes of every weight file.
+pub const MAGIC: &[u8; 4] = b"TFEW";
+
+/// The version of the format written by this crate.
+pub const VERSION: u16 = 1;
+
+/// The largest number of bits a pattern can be indexed by, `tile bits * pattern length`.
+/// A pattern of this size has 64MB of weights, enough for 6-tuples of 4 bit tiles.
+pub const MAX_PATTERN_BITS: usize = 24;
+
+/// Struct that contains the pattern layout and weights stored in a weight file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct WeightFile {
+    pub tile_bits: u8,
+    pub patterns:  Vec<Vec<u8>>,
+    pub weights:   Vec<Vec<f32>>
+}
+
+/// Returns the 32 bit FNV-1a hash of `weights` stored as little-endian bytes.
+fn checksum(weights: &[Vec<f32>]) -> u32 {
+    let mut hash = 0x811C_9DC5_u32;
+
+    for weight in weights.iter().flat_map(|w| w.iter()) {
+        for byte in weight.to_le_bytes().iter() {
+            hash ^= *byte as u32;
+            hash  = hash.wrapping_mul(0x0100_0193);
+        }
+    }
+
+    hash
+}
+
+/// Returns the number of weights of a pattern of `len` cells with `tile_bits` bits per tile.
+/// Fails when the pattern is indexed by more than `tfe::weights::MAX_PATTERN_BITS` bits.
+pub(crate) fn table_len(tile_bits: u8, len: usize) -> io::Result<usize> {
+    if tile_bits == 0 || tile_bits > 8 { return Err(invalid("invalid tile bit width")) }
+    if tile_bits as usize * len > MAX_PATTERN_BITS { return Err(invalid("pattern too large")) }
+
+    Ok(1 << (tile_bits as usize * len))
+}
+
+impl WeightFile {
+    /// Writes this weight file to `writer`.
+    /// Fails without writing anything when `tfe::weights::WeightFile::read` could not read it back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::weights::WeightFile;
+    ///
+    /// // a pattern of a single 4 bit tile needs 16 weights
+    /// let file = WeightFile { tile_bits: 4, patterns: vec![vec![0]], weights: vec![vec![1.5; 15]] };
+    ///
+    /// assert!(file.write(&mut vec![]).is_err());
+    /// ```
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.patterns.len() != self.weights.len() {
+            return Err(invalid("every pattern needs exactly one list of weights"));
+        }
+
+        if self.patterns.len() > u16::MAX as usize { return Err(invalid("too many patterns")) }
+
+        for (pattern, weights) in self.patterns.iter().zip(&self.weights) {
+            if weights.len() != table_len(self.tile_bits, pattern.len())? {
+                return Err(invalid("pattern has the wrong number of weights"));
+            }
+        }
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&[self.tile_bits])?;
+        writer.write_all(&(self.patterns.len() as u16).to_le_bytes())?;
+
+        for pattern in &self.patterns {
+            writer.write_all(&[pattern.len() as u8])?;
+            writer.write_all(pattern)?;
+        }
+
+        writer.write_all(&checksum(&self.weights).to_le_bytes())?;
+
+        for weight in self.weights.iter().flat_map(|w| w.iter()) {
+            writer.write_all(&weight.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a weight file from `reader`, verifying its version, size and checksum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tfe::weights::WeightFile;
+    ///
+    /// // the header of a 6-tuple with 64MB of weights, followed by none of them
+    /// let mut bytes = b"TFEW\x01\x00\x04\x01\x00\x06".to_vec();
+    /// bytes.extend(&[0, 1, 2, 3, 4, 5, 0, 0, 0, 0]);
+    ///
+    /// assert!(WeightFile::read(&mut &bytes[..]).is_err());
+    /// ```
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        if &read_bytes::<_, 4>(reader)? != MAGIC { return Err(invalid("not a weight file")) }
+
+        let version = u16::from_le_bytes(read_bytes(reader)?);
+
+        if version != VERSION {
+            return Err(invalid(&format!("unsupported weight file version: {}", version)));
+        }
+
+        let [tile_bits] = read_bytes::<_, 1>(reader)?;
+        let count       = u16::from_le_bytes(read_bytes(reader)?);
+        let mut patterns = Vec::with_capacity(count as usize);
+
+        table_len(tile_bits, 0)?;
+
+        for _ in 0 .. count {
+            let [len]       = read_bytes::<_, 1>(reader)?;
+            let mut pattern = vec![0; len as usize];
+
+            table_len(tile_bits, pattern.len())?;
+
+            reader.read_exact(&mut pattern)?;
+            patterns.push(pattern);
+        }
+
+        let expected    = u32::from_le_bytes(read_bytes(reader)?);
+        let mut weights = Vec::with_capacity(patterns.len());
+
+        for pattern in &patterns {
+            let len       = 4 * table_len(tile_bits, pattern.len())?;
+            let mut bytes = vec![];
+
+            // the header is not trusted for allocating, weights are only kept once they are read
+            reader.take(len as u64).read_to_end(&mut bytes)?;
+
+            if bytes.len() < len { return Err(invalid("weight file ends before its last weight")) }
+
+            weights.push(bytes.chunks(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect());
+        }
+
+        if checksum(&weights) != expected { return Err(invalid("weight file checksum mismatch")) }
+
+        Ok(WeightFile { tile_bits, patterns, weights })
+    }
+
+    /// Writes this weight file to `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads a weight file from `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Returns `true` if the file at `path` starts with `tfe::weights::MAGIC`.
+    pub fn is_weight_file<P: AsRef<Path>>(path: P) -> bool {
+        File::open(path).and_then(|mut file| read_bytes::<_, 4>(&mut file)).map(|magic| &magic == MAGIC).unwrap_or(false)
+    }
+}